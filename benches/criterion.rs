@@ -3,17 +3,17 @@ use criterion::{black_box, criterion_group, criterion_main, Bencher, Criterion};
 use rand::seq::SliceRandom;
 use rand::SeedableRng;
 use rand_pcg::Pcg64;
-use std::fmt::Debug;
 
 extern crate bt;
 use bt::arena::Tree;
+use bt::slab;
 
 const K: usize = 256;
 
 #[inline]
 fn insert<T>(values: &Vec<T>)
 where
-    T: Ord + Copy + Default + Debug,
+    T: Ord + Copy,
 {
     let mut t = Tree::<T, K>::default();
     for &v in values {
@@ -24,7 +24,7 @@ where
 #[inline]
 fn insert_delete<T>(values: &Vec<T>, delete_values: &Vec<T>)
 where
-    T: Ord + Copy + Default + Debug,
+    T: Ord + Copy,
 {
     let mut t = Tree::<_, K>::default();
     for &v in values {
@@ -32,7 +32,33 @@ where
     }
 
     for &v in delete_values {
-        t.delete(v);
+        t.delete(&v);
+    }
+}
+
+#[inline]
+fn insert_slab<T>(values: &Vec<T>)
+where
+    T: Ord + Copy,
+{
+    let mut t = slab::Tree::<T>::with_order(K);
+    for &v in values {
+        t.insert(v);
+    }
+}
+
+#[inline]
+fn insert_delete_slab<T>(values: &Vec<T>, delete_values: &Vec<T>)
+where
+    T: Ord + Copy,
+{
+    let mut t = slab::Tree::<_>::with_order(K);
+    for &v in values {
+        t.insert(v);
+    }
+
+    for &v in delete_values {
+        t.delete(&v);
     }
 }
 
@@ -41,7 +67,7 @@ fn rand_vec(n: u64, seed: usize) -> Vec<u64> {
     let mut rng = Pcg64::seed_from_u64(seed as u64);
     let mut vec: Vec<_> = (0..n).collect();
     vec.shuffle(&mut rng);
-    return vec;
+    vec
 }
 
 fn benchmark_rand_insert(b: &mut Bencher, n: u64, seed: usize) {
@@ -55,11 +81,27 @@ fn benchmark_rand_insert_delete_half(b: &mut Bencher, n: u64, seed: usize) {
     b.iter(|| insert_delete(black_box(&vec), black_box(&vec_to_delete)))
 }
 
+fn benchmark_rand_insert_slab(b: &mut Bencher, n: u64, seed: usize) {
+    let vec = rand_vec(n, seed);
+    b.iter(|| insert_slab(black_box(&vec)))
+}
+
+fn benchmark_rand_insert_delete_half_slab(b: &mut Bencher, n: u64, seed: usize) {
+    let vec = rand_vec(n, seed);
+    let vec_to_delete = rand_vec(n / 2, seed + 1);
+    b.iter(|| insert_delete_slab(black_box(&vec), black_box(&vec_to_delete)))
+}
+
 fn benchmark_seq_insert(b: &mut Bencher, n: usize) {
     let vec: Vec<_> = (0_u64..n as u64).collect();
     b.iter(|| insert(&vec))
 }
 
+fn benchmark_seq_bulk_load(b: &mut Bencher, n: usize) {
+    let vec: Vec<_> = (0_u64..n as u64).collect();
+    b.iter(|| Tree::<_, K>::from_sorted_iter(black_box(&vec).iter().copied()))
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     let mut group = c.benchmark_group("seq_insert");
     for size in [1_000, 1_000_000].iter() {
@@ -69,6 +111,14 @@ fn criterion_benchmark(c: &mut Criterion) {
     }
     group.finish();
 
+    let mut group = c.benchmark_group("seq_bulk_load");
+    for size in [1_000, 1_000_000].iter() {
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &s| {
+            benchmark_seq_bulk_load(b, *s as usize);
+        });
+    }
+    group.finish();
+
     const DEFAULT_SEED: usize = 1024;
     let mut group = c.benchmark_group("rand_insert");
     for size in [1_000, 1_000_000].iter() {
@@ -85,6 +135,22 @@ fn criterion_benchmark(c: &mut Criterion) {
         });
     }
     group.finish();
+
+    let mut group = c.benchmark_group("rand_insert_slab");
+    for size in [1_000, 1_000_000].iter() {
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &s| {
+            benchmark_rand_insert_slab(b, *s as u64, DEFAULT_SEED);
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("rand_insert_delete_half_slab");
+    for size in [1_000, 1_000_000].iter() {
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &s| {
+            benchmark_rand_insert_delete_half_slab(b, *s as u64, DEFAULT_SEED);
+        });
+    }
+    group.finish();
 }
 
 criterion_group!(benches, criterion_benchmark);
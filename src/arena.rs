@@ -1,38 +1,71 @@
-use std::cmp::Ordering;
-use std::fmt::Debug;
+use alloc::collections::TryReserveError;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt::{self, Debug};
+use core::ops::{Bound, RangeBounds};
 
 extern crate arrayvec;
 use arrayvec::ArrayVec;
 
+mod bulk;
+mod compare;
+mod cursor;
+mod extract;
+mod fixed;
+mod iter;
+mod map;
+mod multiset;
+mod order;
+#[cfg(feature = "serde")]
+mod serialize;
+mod set;
+mod split;
+mod storage;
+mod summary;
+mod validate;
+pub use compare::{Compare, Natural};
+pub use cursor::{Cursor, CursorMut};
+pub use extract::ExtractIf;
+pub use fixed::{CapacityError, StaticTree};
+pub use iter::{IntoIter, Iter, Range};
+pub use map::{Entry, OccupiedEntry, TreeMap, VacantEntry};
+pub use multiset::MultiSet;
+pub use set::{Difference, Intersection, SymmetricDifference, Union};
+pub use storage::{Buffer, Fixed, Heap, Storage};
+pub use summary::{Size, SubtreeSize, Summary};
+pub use validate::{Invariant, InvariantViolation};
+
 #[derive(Debug)]
-pub struct Node<T, const M: usize>
-where
-    [(); M - 1]: Sized,
-{
+pub struct Node<T, const M: usize, S = ()> {
     idx: usize,
     parent: Option<usize>,
-    values: ArrayVec<T, { M - 1 }>,
+    /// The summary of the values in the subtree rooted at this node.
+    summary: S,
+    /// Holds at most `M - 1` values, the last slot is never used.
+    values: ArrayVec<T, M>,
     children: ArrayVec<usize, M>,
 }
 
-impl<T, const M: usize> Default for Node<T, M>
+impl<T, const M: usize, S> Default for Node<T, M, S>
 where
-    [(); M - 1]: Sized,
+    S: Summary<T>,
 {
     fn default() -> Self {
         Node {
             idx: 0,
             parent: None,
+            summary: S::empty(),
             values: ArrayVec::new(),
             children: ArrayVec::new(),
         }
     }
 }
 
-impl<T, const M: usize> Node<T, M>
-where
-    [(); M - 1]: Sized,
-{
+impl<T, const M: usize, S> Node<T, M, S> {
     pub fn is_root(&self) -> bool {
         self.parent.is_none()
     }
@@ -40,29 +73,82 @@ where
     pub fn is_leaf(&self) -> bool {
         self.children.is_empty()
     }
+
+    /// Tells whether the node holds `M - 1` values, which is as many as it can.
+    pub fn is_full(&self) -> bool {
+        self.values.len() == M - 1
+    }
 }
 
-#[derive(Debug)]
-pub struct Tree<T, const M: usize>
+pub struct Tree<T, const M: usize, S = (), C = Natural, A: Storage = Heap> {
+    arena: A::Buf<Node<T, M, S>>,
+    root_id: usize,
+    len: usize,
+    /// Indexes of the vacant nodes in the arena which can be reused.
+    free: A::Buf<usize>,
+    /// The comparator every value in the tree is ordered by.
+    cmp: C,
+}
+
+impl<T, const M: usize, S, C, A> Debug for Tree<T, M, S, C, A>
 where
-    [(); M - 1]: Sized,
+    T: Debug,
+    S: Debug,
+    C: Debug,
+    A: Storage,
 {
-    arena: Vec<Node<T, M>>,
-    root_id: usize,
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tree")
+            .field("arena", &&*self.arena)
+            .field("root_id", &self.root_id)
+            .field("len", &self.len)
+            .field("free", &&*self.free)
+            .field("cmp", &self.cmp)
+            .finish()
+    }
 }
 
-impl<T, const M: usize> Default for Tree<T, M>
+impl<T, const M: usize, S, C, A> Default for Tree<T, M, S, C, A>
 where
-    T: Ord + Copy + Default + Debug,
-    [(); M - 1]: Sized,
+    C: Compare<T> + Default,
+    S: Summary<T>,
+    A: Storage,
 {
     fn default() -> Self {
-        let mut t = Tree::<T, M> {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T, const M: usize, S, C> Tree<T, M, S, C>
+where
+    C: Compare<T> + Default,
+    S: Summary<T>,
+{
+    /// Creates an empty tree with room for at least `nodes` nodes in the arena.
+    pub fn with_capacity(nodes: usize) -> Self {
+        let mut t = Self::default();
+        t.reserve(nodes.saturating_sub(t.arena.len()));
+        t
+    }
+}
+
+impl<T, const M: usize, S, C, A> Tree<T, M, S, C, A>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+    A: Storage,
+{
+    /// Creates an empty tree ordering its values by the comparator.
+    pub fn with_comparator(cmp: C) -> Self {
+        let mut t = Self {
             root_id: 0,
-            arena: Vec::new(),
+            arena: Default::default(),
+            len: 0,
+            free: Default::default(),
+            cmp,
         };
         let root_id = t.arena.len();
-        let root = Node::<T, M> {
+        let root = Node::<T, M, S> {
             idx: root_id,
             ..Default::default()
         };
@@ -72,32 +158,78 @@ where
     }
 }
 
-impl<T, const M: usize> Tree<T, M>
+impl<T, const M: usize, S, C, A> Tree<T, M, S, C, A>
 where
-    T: Ord + Copy + Default + Debug,
-    [(); M - 1]: Sized,
+    C: Compare<T>,
+    S: Summary<T>,
+    A: Storage,
 {
-    fn insert_into(&mut self, cur_id: usize, value: T) -> Option<(T, usize)> {
+    /// Takes a vacant node from the free list, or pushes a new one into the arena.
+    fn new_node(&mut self) -> usize {
+        if let Some(node_id) = self.free.pop() {
+            return node_id;
+        }
+        let node_id = self.arena.len();
+        self.arena.push(Node::<T, M, S> {
+            idx: node_id,
+            ..Default::default()
+        });
+        node_id
+    }
+
+    /// Empties the node and puts it into the free list for reusing.
+    fn free_node(&mut self, node_id: usize) {
+        let node = &mut self.arena[node_id];
+        node.parent = None;
+        node.summary = S::empty();
+        node.values.clear();
+        node.children.clear();
+        self.free.push(node_id);
+    }
+
+    /// Recomputes the summary of the node from its values and children in order.
+    fn update_summary(&mut self, node_id: usize) {
+        if core::mem::size_of::<S>() == 0 {
+            // a zero-sized summary, like the default `()`, has only one value
+            return;
+        }
+        let node = &self.arena[node_id];
+        let mut summary = match node.children.first() {
+            Some(&child_id) => self.arena[child_id].summary.clone(),
+            None => S::empty(),
+        };
+        for (idx, value) in node.values.iter().enumerate() {
+            summary = summary.combine(&S::from_value(value));
+            if let Some(&child_id) = node.children.get(idx + 1) {
+                summary = summary.combine(&self.arena[child_id].summary);
+            }
+        }
+        self.arena[node_id].summary = summary;
+    }
+
+    /// Inserts the value into the subtree of `cur_id`.
+    /// Returns the median value and the new right node if the node was separated,
+    /// or gives the value back if it already exists.
+    fn insert_into(&mut self, cur_id: usize, value: T) -> Result<Option<(T, usize)>, T> {
         let cur = &self.arena[cur_id];
-        let (insert_idx, found) = Self::binary_search(&cur.values, value);
+        let (insert_idx, found) = Self::binary_search(&self.cmp, &cur.values, &value);
         if found {
-            return None;
+            return Err(value);
         }
         let (value, value_right_child_id) = if cur.is_leaf() {
             (value, None)
         } else {
             let child_id = self.arena[cur_id].children[insert_idx];
-            if let Some((median, median_right_child_id)) = self.insert_into(child_id, value) {
+            if let Some((median, median_right_child_id)) = self.insert_into(child_id, value)? {
                 (median, Some(median_right_child_id))
             } else {
-                return None;
+                self.update_summary(cur_id);
+                return Ok(None);
             }
         };
 
         #[cfg(debug_assertions)]
         if let Some(child_id) = value_right_child_id {
-            debug!(value, &self.arena[child_id], &self.arena[cur_id]);
-
             assert_eq!(
                 self.arena[cur_id].values.len() + 1,
                 self.arena[cur_id].children.len()
@@ -112,230 +244,350 @@ where
         }
 
         let cur = &self.arena[cur_id];
-        if !cur.values.is_full() {
+        if !cur.is_full() {
             let cur = &mut self.arena[cur_id];
             cur.values.insert(insert_idx, value);
             if let Some(child_id) = value_right_child_id {
                 cur.children.insert(insert_idx + 1, child_id);
                 self.arena[child_id].parent = Some(cur_id);
             }
-            None
+            self.update_summary(cur_id);
+            Ok(None)
         } else {
-            // need to separate node
-            let (right, median) = {
-                let right_id = self.arena.len();
-                let mut right = Node::<T, M> {
-                    idx: right_id,
-                    ..Default::default()
-                };
-                let cur = &mut self.arena[cur_id];
-                match insert_idx.cmp(&(M / 2)) {
-                    Ordering::Greater => {
-                        // values: | left | median | right |
-
-                        // left: 0..M / 2
-                        // M / 2
-                        // median: M / 2
-                        // right: M / 2 + 1..M - 1
-
-                        // right: | part one | value | part two |
-                        // part one: M / 2 + 1..insert_idx
-                        // value
-                        // part two: insert_idx..M - 1
-                        // M / 2 - 1
+            Ok(Some(self.split_insert(
+                cur_id,
+                insert_idx,
+                value,
+                value_right_child_id,
+            )))
+        }
+    }
+
+    /// Separates the full node into two while inserting the value with its right child.
+    /// Returns the median value and the new right node.
+    fn split_insert(
+        &mut self,
+        cur_id: usize,
+        insert_idx: usize,
+        value: T,
+        value_right_child_id: Option<usize>,
+    ) -> (T, usize) {
+        let (right, median) = {
+            let right_id = self.new_node();
+            let mut right = Node::<T, M, S> {
+                idx: right_id,
+                ..Default::default()
+            };
+            let cur = &mut self.arena[cur_id];
+            match insert_idx.cmp(&(M / 2)) {
+                Ordering::Greater => {
+                    // values: | left | median | right |
+
+                    // left: 0..M / 2
+                    // M / 2
+                    // median: M / 2
+                    // right: M / 2 + 1..M - 1
+
+                    // right: | part one | value | part two |
+                    // part one: M / 2 + 1..insert_idx
+                    // value
+                    // part two: insert_idx..M - 1
+                    // M / 2 - 1
+                    {
+                        let drain = &mut cur.values.drain(M / 2 + 1..);
+                        right.values.extend(drain.take(insert_idx - M / 2 - 1));
+                        right.values.push(value);
+                        right.values.extend(drain);
+                    };
+                    // children: | left | right |
+                    // left: 0..M / 2 + 1
+                    // M / 2 + 1
+                    // right: M / 2 + 1..
+                    // M / 2
+
+                    if let Some(child_id) = value_right_child_id {
                         {
-                            let drain = &mut cur.values.drain(M / 2 + 1..);
-                            right.values.extend(drain.take(insert_idx - M / 2 - 1));
-                            right.values.push(value);
-                            right.values.extend(drain);
-                        };
-                        // children: | left | right |
-                        // left: 0..M / 2 + 1
-                        // M / 2 + 1
-                        // right: M / 2 + 1..
-                        // M / 2
-
-                        if let Some(child_id) = value_right_child_id {
-                            {
-                                let drain = &mut cur.children.drain(M / 2 + 1..);
-                                right.children.extend(drain.take(insert_idx - M / 2));
-                                right.children.push(child_id);
-                                right.children.extend(drain);
-                            }
-                            for &child_id in &right.children {
-                                self.arena[child_id].parent = Some(right_id);
-                            }
+                            let drain = &mut cur.children.drain(M / 2 + 1..);
+                            right.children.extend(drain.take(insert_idx - M / 2));
+                            right.children.push(child_id);
+                            right.children.extend(drain);
+                        }
+                        for &child_id in &right.children {
+                            self.arena[child_id].parent = Some(right_id);
                         }
-
-                        (right, self.arena[cur_id].values.remove(M / 2))
                     }
-                    Ordering::Less => {
-                        // values: | left | median | right |
-
-                        // left: | part one | value | part two |
-                        // part one: 0..insert_idx
-                        // value
-                        // part two: insert_idx..M / 2 - 1
-                        // M / 2
-
-                        // median: M / 2 - 1
-                        // right: M / 2..
-                        // M / 2 - 1
-
-                        right.values.extend(cur.values.drain(M / 2..));
-                        let median = cur.values.remove(M / 2 - 1);
-                        cur.values.insert(insert_idx, value);
-
-                        // children: | left | right |
-
-                        // left: | part one | child_id | part two |
-                        // part one: 0..insert_idx + 1
-                        // child_id: insert_idx + 1
-                        // part two: insert_idx + 1..M / 2
-                        // M / 2 + 1
-
-                        // right: M / 2 ..
-                        // M / 2
-
-                        if let Some(child_id) = value_right_child_id {
-                            right.children.extend(cur.children.drain(M / 2..));
-                            for &child_id in &right.children {
-                                self.arena[child_id].parent = Some(right_id);
-                            }
-
-                            let cur = &mut self.arena[cur_id];
-                            cur.children.insert(insert_idx + 1, child_id);
-                            self.arena[child_id].parent = Some(cur_id);
+
+                    (right, self.arena[cur_id].values.remove(M / 2))
+                }
+                Ordering::Less => {
+                    // values: | left | median | right |
+
+                    // left: | part one | value | part two |
+                    // part one: 0..insert_idx
+                    // value
+                    // part two: insert_idx..M / 2 - 1
+                    // M / 2
+
+                    // median: M / 2 - 1
+                    // right: M / 2..
+                    // M / 2 - 1
+
+                    right.values.extend(cur.values.drain(M / 2..));
+                    let median = cur.values.remove(M / 2 - 1);
+                    cur.values.insert(insert_idx, value);
+
+                    // children: | left | right |
+
+                    // left: | part one | child_id | part two |
+                    // part one: 0..insert_idx + 1
+                    // child_id: insert_idx + 1
+                    // part two: insert_idx + 1..M / 2
+                    // M / 2 + 1
+
+                    // right: M / 2 ..
+                    // M / 2
+
+                    if let Some(child_id) = value_right_child_id {
+                        right.children.extend(cur.children.drain(M / 2..));
+                        for &child_id in &right.children {
+                            self.arena[child_id].parent = Some(right_id);
                         }
 
-                        (right, median)
+                        let cur = &mut self.arena[cur_id];
+                        cur.children.insert(insert_idx + 1, child_id);
+                        self.arena[child_id].parent = Some(cur_id);
                     }
-                    Ordering::Equal => {
-                        // values: | left | median | right |
-                        // left: 0..M / 2
-                        // M / 2
-                        // median: value
-                        // right: M / 2..M - 1
-                        // M / 2 - 1
 
-                        right.values.extend(cur.values.drain(M / 2..));
+                    (right, median)
+                }
+                Ordering::Equal => {
+                    // values: | left | median | right |
+                    // left: 0..M / 2
+                    // M / 2
+                    // median: value
+                    // right: M / 2..M - 1
+                    // M / 2 - 1
+
+                    right.values.extend(cur.values.drain(M / 2..));
+
+                    // children: | left | right |
+
+                    // left: 0..M / 2
+                    // child_id
+                    // M / 2 + 1
+
+                    // right: M / 2..
+                    // M / 2
+
+                    if let Some(child_id) = value_right_child_id {
+                        right.children.push(child_id);
+                        right.children.extend(cur.children.drain(M / 2 + 1..));
+                        for &child_id in &right.children {
+                            self.arena[child_id].parent = Some(right_id);
+                        }
+                    }
+
+                    (right, value)
+                }
+            }
+        };
+        let right_id = right.idx;
+        self.arena[right_id] = right;
+        self.update_summary(cur_id);
+        self.update_summary(right_id);
+        (median, right_id)
+    }
+
+    /// Inserts the value with its right child at `idx` of the node,
+    /// then separates the nodes upwards as long as they overflow.
+    /// It walks up through the parents only while splitting,
+    /// unless the summaries of the ancestors have to be recomputed.
+    /// Returns the position where the value ends up.
+    fn insert_at(
+        &mut self,
+        node_id: usize,
+        idx: usize,
+        value: T,
+        right_child_id: Option<usize>,
+    ) -> (usize, usize) {
+        let (mut cur_id, mut idx, mut value, mut right_child_id) =
+            (node_id, idx, value, right_child_id);
+        // the value stays in one of the separated nodes, unless it becomes the median
+        let mut handle = None;
+        while self.arena[cur_id].is_full() {
+            let (median, right_id) = self.split_insert(cur_id, idx, value, right_child_id);
+            if handle.is_none() {
+                handle = match idx.cmp(&(M / 2)) {
+                    Ordering::Greater => Some((right_id, idx - M / 2 - 1)),
+                    Ordering::Less => Some((cur_id, idx)),
+                    Ordering::Equal => None,
+                };
+            }
+            match self.sibling(cur_id) {
+                (_, Some(child_idx), _) => {
+                    cur_id = self.arena[cur_id].parent.unwrap();
+                    idx = child_idx;
+                    value = median;
+                    right_child_id = Some(right_id);
+                }
+                _ => {
+                    self.new_root(median, right_id);
+                    return handle.unwrap_or((self.root_id, 0));
+                }
+            }
+        }
 
-                        // children: | left | right |
+        let cur = &mut self.arena[cur_id];
+        cur.values.insert(idx, value);
+        if let Some(child_id) = right_child_id {
+            cur.children.insert(idx + 1, child_id);
+            self.arena[child_id].parent = Some(cur_id);
+        }
+        self.update_summaries_up(cur_id);
+        handle.unwrap_or((cur_id, idx))
+    }
 
-                        // left: 0..M / 2
-                        // child_id
-                        // M / 2 + 1
+    /// Grows the tree by one level with a new root holding the value,
+    /// the current root as its left child and the given node as its right child.
+    fn new_root(&mut self, value: T, right_id: usize) {
+        let root_id = self.new_node();
+        let root = &mut self.arena[root_id];
+        root.values.push(value);
+        root.children.push(self.root_id);
+        root.children.push(right_id);
 
-                        // right: M / 2..
-                        // M / 2
+        self.arena[self.root_id].parent = Some(root_id);
+        self.arena[right_id].parent = Some(root_id);
 
-                        if let Some(child_id) = value_right_child_id {
-                            right.children.push(child_id);
-                            right.children.extend(cur.children.drain(M / 2 + 1..));
-                            for &child_id in &right.children {
-                                self.arena[child_id].parent = Some(right_id);
-                            }
-                        }
+        self.update_summary(root_id);
+        self.root_id = root_id;
+    }
 
-                        (right, value)
-                    }
-                }
-            };
-            assert_eq!(right.idx, self.arena.len());
-            self.arena.push(right);
-            Some((median, self.arena.len() - 1))
+    /// Counts the new nodes inserting a value into the leaf can take,
+    /// one for every full node separating on the way up and one for a new root.
+    fn nodes_for_insert(&self, leaf_id: usize) -> usize {
+        let mut count = 0;
+        let mut cur_id = Some(leaf_id);
+        while let Some(id) = cur_id {
+            if !self.arena[id].is_full() {
+                return count;
+            }
+            count += 1;
+            cur_id = self.arena[id].parent;
         }
+        count + 1
     }
 
-    pub fn insert(&mut self, value: T) {
-        debug!(value);
-        if let Some((median, right_id)) = self.insert_into(self.root_id, value) {
-            debug!(median, &self.arena[right_id], &self.arena[self.root_id]);
-            let root_id = self.arena.len();
-            let mut root = Node::<T, M> {
-                idx: root_id,
-                ..Default::default()
-            };
-            root.values.push(median);
-            root.children.push(self.root_id);
-            root.children.push(right_id);
+    /// Returns the number of values in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
 
-            self.arena[self.root_id].parent = Some(root_id);
-            self.arena[right_id].parent = Some(root_id);
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
 
-            self.arena.push(root);
-            self.root_id = root_id;
-        };
+    fn binary_search<Q, const N: usize>(cmp: &C, array: &ArrayVec<T, N>, value: &Q) -> (usize, bool)
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        Self::binary_search_by(array, &|v: &T| cmp.compare(v.borrow(), value))
+    }
+
+    /// Creates an empty tree ordered by the same comparator.
+    fn new_empty(&self) -> Self
+    where
+        C: Clone,
+    {
+        Self::with_comparator(self.cmp.clone())
+    }
+
+    /// Tells whether the comparator orders `a` before `b`.
+    fn less(&self, a: &T, b: &T) -> bool {
+        self.cmp.compare(a, b) == Ordering::Less
     }
 
-    fn binary_search<const N: usize>(array: &ArrayVec<T, N>, value: T) -> (usize, bool) {
+    /// Searches with a comparator function which returns the ordering of
+    /// the element compared with the target, like `slice::binary_search_by`.
+    fn binary_search_by<F, const N: usize>(array: &ArrayVec<T, N>, f: &F) -> (usize, bool)
+    where
+        F: Fn(&T) -> Ordering,
+    {
         let mut low = 0;
         let mut high = array.len();
         let mut median = ((high - low) / 2) + low;
         while low < high {
-            match value.cmp(&array[median]) {
-                Ordering::Less => high = median,
+            match f(&array[median]) {
+                Ordering::Greater => high = median,
                 Ordering::Equal => return (median, true),
-                Ordering::Greater => low = median + 1,
+                Ordering::Less => low = median + 1,
             };
             median = ((high - low) / 2) + low;
         }
         (median, false)
     }
 
-    fn delete_into(&mut self, node_id: usize, val: T) -> Option<T> {
-        let cur = &self.arena[node_id];
-        let (index, found) = Self::binary_search(&cur.values, val);
-        if found {
-            let cur = &mut self.arena[node_id];
-
-            // find the correct value to fix vacant space
-            let (from_id, deleted_value) = if cur.is_leaf() {
-                (cur.idx, cur.values.remove(index))
-            } else {
-                let (from_id, from_index) = match self.adjacent_children(node_id, index) {
-                    (Some(&left_id), None) => {
-                        let (most_right_id, _) = self.most_right(left_id);
-                        (most_right_id, self.arena[most_right_id].values.len() - 1)
-                    }
-                    (Some(&left_id), Some(&right_id)) => {
-                        let (most_right_id, most_right_depth) = self.most_right(left_id);
-                        let (most_left_id, most_left_depth) = self.most_left(right_id);
-                        // Taking the value from the less depth node
-                        // can rebalance faster than taking the other way.
-                        if most_left_depth > most_right_depth {
-                            (most_right_id, self.arena[most_right_id].values.len() - 1)
-                        } else {
-                            (most_left_id, 0)
-                        }
-                    }
-                    (None, _) => unreachable!(),
-                };
-
-                let new_separator_value = {
-                    let from_node = &mut self.arena[from_id];
-                    from_node.values.remove(from_index)
-                };
-
-                let node = &mut self.arena[node_id];
-                let deleted_value = std::mem::replace(&mut node.values[index], new_separator_value);
+    /// Updates the summaries from the leaf which lost a value up to the root,
+    /// then rebalances the leaf.
+    fn removed_from_leaf(&mut self, leaf_id: usize) {
+        self.update_summaries_up(leaf_id);
+        self.refill(leaf_id);
+    }
 
-                (from_id, deleted_value)
-            };
+    /// Recomputes the summaries from the node up to the root.
+    fn update_summaries_up(&mut self, node_id: usize) {
+        if core::mem::size_of::<S>() == 0 {
+            return;
+        }
+        let mut cur_id = Some(node_id);
+        while let Some(id) = cur_id {
+            self.update_summary(id);
+            cur_id = self.arena[id].parent;
+        }
+    }
 
-            self.rebalance(from_id);
+    /// Removes the value at the position in a leaf.
+    fn remove_leaf_handle(&mut self, (leaf_id, idx): (usize, usize)) -> T {
+        let value = self.arena[leaf_id].values.remove(idx);
+        self.removed_from_leaf(leaf_id);
+        self.len -= 1;
+        value
+    }
 
-            Some(deleted_value)
-        } else if !cur.is_leaf() {
-            let child_id = cur.children[index];
-            self.delete_into(child_id, val)
-        } else {
-            None
+    /// Removes the value at the position. A value in an internal node
+    /// is replaced by its predecessor, which is taken from a leaf.
+    fn remove_handle(&mut self, (node_id, idx): (usize, usize)) -> T {
+        let node = &self.arena[node_id];
+        if node.is_leaf() {
+            return self.remove_leaf_handle((node_id, idx));
         }
+        let (leaf_id, _) = self.most_right(node.children[idx]);
+        let predecessor = self.arena[leaf_id].values.pop().unwrap();
+        let value = core::mem::replace(&mut self.arena[node_id].values[idx], predecessor);
+        self.removed_from_leaf(leaf_id);
+        self.len -= 1;
+        value
+    }
+
+    /// Removes the value equals to the given one from the tree and returns it.
+    pub fn delete<Q>(&mut self, val: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        let handle = self.get_handle_by(&|v: &T| self.cmp.compare(v.borrow(), val))?;
+        Some(self.remove_handle(handle))
     }
 
-    pub fn delete(&mut self, val: T) -> Option<T> {
-        self.delete_into(self.root_id, val)
+    fn remove_by<F>(&mut self, f: &F) -> Option<T>
+    where
+        F: Fn(&T) -> Ordering,
+    {
+        let handle = self.get_handle_by(f)?;
+        Some(self.remove_handle(handle))
     }
 
     fn rebalance(&mut self, node_id: usize) {
@@ -373,7 +625,7 @@ where
                 return;
             }
 
-            let (merged_node_id, _deficient_node_id) = if right_len > 0 && left_len > 0 {
+            let (merged_node_id, emptied_node_id) = if right_len > 0 && left_len > 0 {
                 // Node merges a minor sibling node
                 if is_left_max {
                     self.merge_right(cur_id)
@@ -391,16 +643,32 @@ where
             };
 
             let parent_id = self.arena[cur_id].parent.unwrap();
+            self.free_node(emptied_node_id);
             let parent = &self.arena[parent_id];
             if parent.values.is_empty() && parent.is_root() {
                 self.arena[merged_node_id].parent = None;
                 self.root_id = merged_node_id;
+                self.free_node(parent_id);
                 return;
             }
             cur_id = parent_id;
         }
     }
 
+    /// Rebalances the node until it holds enough values.
+    /// Unlike a node which lost one value, it may lack more than one,
+    /// so it takes a value from a sibling repeatedly before merging with it.
+    fn refill(&mut self, node_id: usize) {
+        loop {
+            // a merged away node has been freed and detached
+            let node = &self.arena[node_id];
+            if node.is_root() || node.values.len() >= (M - 1) / 2 {
+                return;
+            }
+            self.rebalance(node_id);
+        }
+    }
+
     fn rotate_left(&mut self, node_id: usize) {
         let parent_id = self.arena[node_id].parent.unwrap();
         if let (_, Some(node_idx), Some(right_id)) = self.sibling(node_id) {
@@ -419,6 +687,9 @@ where
                 self.arena[node_id].children.push(child_id);
                 self.arena[child_id].parent = Some(node_id);
             }
+            self.update_summary(node_id);
+            self.update_summary(right_id);
+            self.update_summary(parent_id);
         } else {
             unreachable!()
         };
@@ -429,7 +700,7 @@ where
         if let (Some(left_id), Some(node_idx), _) = self.sibling(node_id) {
             let value_idx = node_idx - 1;
             let separator = self.arena[parent_id].values.remove(value_idx);
-            self.arena[node_id].values.push(separator);
+            self.arena[node_id].values.insert(0, separator);
             let new_separator = {
                 let left = &mut self.arena[left_id];
                 left.values.remove(left.values.len() - 1)
@@ -445,6 +716,9 @@ where
                 self.arena[node_id].children.insert(0, child_id);
                 self.arena[child_id].parent = Some(node_id);
             }
+            self.update_summary(node_id);
+            self.update_summary(left_id);
+            self.update_summary(parent_id);
         } else {
             unreachable!()
         };
@@ -485,6 +759,8 @@ where
             }
             self.arena[node_id].children.extend(right_children);
         }
+        self.update_summary(node_id);
+        self.update_summary(parent_id);
     }
 
     fn sibling(&self, node_id: usize) -> (Option<usize>, Option<usize>, Option<usize>) {
@@ -517,26 +793,6 @@ where
         }
     }
 
-    fn adjacent_children(
-        &self,
-        node_id: usize,
-        value_idx: usize,
-    ) -> (Option<&usize>, Option<&usize>) {
-        let cur = &self.arena[node_id];
-        if value_idx < cur.children.len() {
-            (
-                Some(&cur.children[value_idx]),
-                if value_idx < cur.children.len() - 1 {
-                    Some(&cur.children[value_idx + 1])
-                } else {
-                    None
-                },
-            )
-        } else {
-            (None, None)
-        }
-    }
-
     fn most_left(&self, node_id: usize) -> (usize, usize) {
         let mut depth = 0;
         let mut cur = &self.arena[node_id];
@@ -557,33 +813,289 @@ where
         (cur.idx, depth)
     }
 
-    pub fn range(&self, _begin: T, _end: T) -> Vec<T> {
-        todo!();
+    fn first_handle(&self) -> Option<(usize, usize)> {
+        let (id, _) = self.most_left(self.root_id);
+        if self.arena[id].values.is_empty() {
+            None
+        } else {
+            Some((id, 0))
+        }
+    }
+
+    fn last_handle(&self) -> Option<(usize, usize)> {
+        let (id, _) = self.most_right(self.root_id);
+        let len = self.arena[id].values.len();
+        if len == 0 {
+            None
+        } else {
+            Some((id, len - 1))
+        }
+    }
+
+    fn value(&self, (node_id, idx): (usize, usize)) -> &T {
+        &self.arena[node_id].values[idx]
+    }
+
+    /// Returns the position of the value following the one at `(node_id, idx)`.
+    fn next_handle(&self, (node_id, idx): (usize, usize)) -> Option<(usize, usize)> {
+        let node = &self.arena[node_id];
+        if !node.is_leaf() {
+            let (id, _) = self.most_left(node.children[idx + 1]);
+            return Some((id, 0));
+        }
+        if idx + 1 < node.values.len() {
+            return Some((node_id, idx + 1));
+        }
+        // climb up until we come from a child which has a separator on its right
+        let mut cur_id = node_id;
+        while let (_, Some(child_idx), _) = self.sibling(cur_id) {
+            let parent_id = self.arena[cur_id].parent.unwrap();
+            if child_idx < self.arena[parent_id].values.len() {
+                return Some((parent_id, child_idx));
+            }
+            cur_id = parent_id;
+        }
+        None
+    }
+
+    /// Returns the position of the value preceding the one at `(node_id, idx)`.
+    fn prev_handle(&self, (node_id, idx): (usize, usize)) -> Option<(usize, usize)> {
+        let node = &self.arena[node_id];
+        if !node.is_leaf() {
+            let (id, _) = self.most_right(node.children[idx]);
+            return Some((id, self.arena[id].values.len() - 1));
+        }
+        if idx > 0 {
+            return Some((node_id, idx - 1));
+        }
+        // climb up until we come from a child which has a separator on its left
+        let mut cur_id = node_id;
+        while let (_, Some(child_idx), _) = self.sibling(cur_id) {
+            let parent_id = self.arena[cur_id].parent.unwrap();
+            if child_idx > 0 {
+                return Some((parent_id, child_idx - 1));
+            }
+            cur_id = parent_id;
+        }
+        None
+    }
+
+    /// Finds the position of the smallest value lying after the `bound`.
+    fn lower_handle<K>(&self, bound: Bound<&K>) -> Option<(usize, usize)>
+    where
+        T: Borrow<K>,
+        C: Compare<K>,
+        K: ?Sized,
+    {
+        let (value, included) = match bound {
+            Bound::Included(value) => (value, true),
+            Bound::Excluded(value) => (value, false),
+            Bound::Unbounded => return self.first_handle(),
+        };
+        let mut candidate = None;
+        let mut cur = &self.arena[self.root_id];
+        loop {
+            let (idx, found) = Self::binary_search(&self.cmp, &cur.values, value);
+            if found && included {
+                return Some((cur.idx, idx));
+            }
+            let idx = if found { idx + 1 } else { idx };
+            if idx < cur.values.len() {
+                candidate = Some((cur.idx, idx));
+            }
+            if cur.is_leaf() {
+                return candidate;
+            }
+            cur = &self.arena[cur.children[idx]];
+        }
+    }
+
+    /// Finds the position of the first value for which the comparator function
+    /// doesn't return `Less`. The function must never return `Equal`,
+    /// so that the descent always ends in a leaf.
+    fn lower_handle_by<F>(&self, f: &F) -> Option<(usize, usize)>
+    where
+        F: Fn(&T) -> Ordering,
+    {
+        let mut candidate = None;
+        let mut cur = &self.arena[self.root_id];
+        loop {
+            let (idx, _) = Self::binary_search_by(&cur.values, f);
+            if idx < cur.values.len() {
+                candidate = Some((cur.idx, idx));
+            }
+            if cur.is_leaf() {
+                return candidate;
+            }
+            cur = &self.arena[cur.children[idx]];
+        }
     }
 
-    pub fn get(&self, value: T) -> Option<T> {
+    /// Finds the position of the largest value lying before the `bound`.
+    fn upper_handle<K>(&self, bound: Bound<&K>) -> Option<(usize, usize)>
+    where
+        T: Borrow<K>,
+        C: Compare<K>,
+        K: ?Sized,
+    {
+        let (value, included) = match bound {
+            Bound::Included(value) => (value, true),
+            Bound::Excluded(value) => (value, false),
+            Bound::Unbounded => return self.last_handle(),
+        };
+        let mut candidate = None;
         let mut cur = &self.arena[self.root_id];
         loop {
-            let (insert_idx, found) = Self::binary_search(&cur.values, value);
+            let (idx, found) = Self::binary_search(&self.cmp, &cur.values, value);
+            if found && included {
+                return Some((cur.idx, idx));
+            }
+            if idx > 0 {
+                candidate = Some((cur.idx, idx - 1));
+            }
+            if cur.is_leaf() {
+                return candidate;
+            }
+            cur = &self.arena[cur.children[idx]];
+        }
+    }
+
+    /// Constructs a double-ended iterator over a sub-range of values in the tree.
+    /// The iterator is lazy, it walks through the nodes while being consumed.
+    pub fn range<K, R>(&self, range: R) -> Range<'_, T, M, S, C, A>
+    where
+        T: Borrow<K>,
+        C: Compare<K>,
+        K: ?Sized,
+        R: RangeBounds<K>,
+    {
+        let front = self.lower_handle(range.start_bound());
+        let back = self.upper_handle(range.end_bound());
+        match (front, back) {
+            (Some((front_id, front_idx)), Some((back_id, back_idx)))
+                if !self.less(
+                    &self.arena[back_id].values[back_idx],
+                    &self.arena[front_id].values[front_idx],
+                ) =>
+            {
+                Range::new(self, front, back)
+            }
+            _ => Range::new(self, None, None),
+        }
+    }
+
+    /// Gets an iterator that visits the values in the tree in ascending order.
+    pub fn iter(&self) -> Iter<'_, T, M, S, C, A> {
+        Iter::new(self.range(..), self.len)
+    }
+
+    /// Returns a reference to the value equals to the given one.
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        self.get_handle_by(&|v: &T| self.cmp.compare(v.borrow(), value))
+            .map(|(node_id, idx)| &self.arena[node_id].values[idx])
+    }
+
+    /// Returns the smallest value in the tree.
+    pub fn first(&self) -> Option<&T> {
+        self.first_handle().map(|handle| self.value(handle))
+    }
+
+    /// Returns the largest value in the tree.
+    pub fn last(&self) -> Option<&T> {
+        self.last_handle().map(|handle| self.value(handle))
+    }
+
+    /// Removes the smallest value from the tree and returns it.
+    pub fn pop_first(&mut self) -> Option<T> {
+        self.first_handle()
+            .map(|handle| self.remove_leaf_handle(handle))
+    }
+
+    /// Removes the largest value from the tree and returns it.
+    pub fn pop_last(&mut self) -> Option<T> {
+        self.last_handle()
+            .map(|handle| self.remove_leaf_handle(handle))
+    }
+
+    /// Finds the position of the value which the comparator function treats as equal.
+    fn get_handle_by<F>(&self, f: &F) -> Option<(usize, usize)>
+    where
+        F: Fn(&T) -> Ordering,
+    {
+        self.search_by(f).ok()
+    }
+
+    /// Descends from the root with the comparator function.
+    /// Returns the position of the equal value,
+    /// or the leaf and the index where it would be inserted.
+    fn search_by<F>(&self, f: &F) -> Result<(usize, usize), (usize, usize)>
+    where
+        F: Fn(&T) -> Ordering,
+    {
+        let mut cur = &self.arena[self.root_id];
+        loop {
+            let (insert_idx, found) = Self::binary_search_by(&cur.values, f);
             if found {
-                return Some(cur.values[insert_idx]);
+                return Ok((cur.idx, insert_idx));
             }
             if !cur.is_leaf() {
                 cur = &self.arena[cur.children[insert_idx]];
                 continue;
             }
-            return None;
+            return Err((cur.idx, insert_idx));
         }
     }
 
-    pub fn traversal_bfs(&self) -> Vec<T> {
-        use std::collections::VecDeque;
+    /// Rewrites the arena densely in BFS order, dropping the vacant nodes.
+    /// Every node index stored in the tree is remapped to its new position.
+    pub fn compact(&mut self) {
+        let mut order = Vec::with_capacity(self.arena.len() - self.free.len());
+        order.push(self.root_id);
+        let mut cur = 0;
+        while cur < order.len() {
+            order.extend_from_slice(&self.arena[order[cur]].children);
+            cur += 1;
+        }
+
+        // the vacant nodes are moved behind the alive ones
+        let mut remap = vec![usize::MAX; self.arena.len()];
+        for (new_id, &old_id) in order.iter().chain(self.free.iter()).enumerate() {
+            remap[old_id] = new_id;
+        }
+        for node in self.arena.iter_mut() {
+            node.idx = remap[node.idx];
+            node.parent = node.parent.map(|id| remap[id]);
+            for child_id in node.children.iter_mut() {
+                *child_id = remap[*child_id];
+            }
+        }
+
+        // move every node to the position its index points to
+        for idx in 0..self.arena.len() {
+            while self.arena[idx].idx != idx {
+                let target = self.arena[idx].idx;
+                self.arena.swap(idx, target);
+            }
+        }
+
+        self.arena.truncate(order.len());
+        self.free.clear();
+        self.root_id = remap[self.root_id];
+    }
+
+    pub fn traversal_bfs(&self) -> Vec<&T> {
+        use alloc::collections::VecDeque;
         let mut q = VecDeque::with_capacity(self.arena.len());
         let mut cur = &self.arena[self.root_id];
 
         let mut path = Vec::new();
         loop {
-            for &val in cur.values.iter() {
+            for val in cur.values.iter() {
                 path.push(val);
             }
             for &child_id in cur.children.iter() {
@@ -596,12 +1108,104 @@ where
         }
         path
     }
+}
+
+impl<T, const M: usize, S, C> Tree<T, M, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    /// Adds a value to the tree.
+    /// Returns whether the value was newly inserted.
+    pub fn insert(&mut self, value: T) -> bool {
+        let separated = match self.insert_into(self.root_id, value) {
+            Ok(separated) => separated,
+            Err(_) => return false,
+        };
+        self.len += 1;
+        if let Some((median, right_id)) = separated {
+            self.new_root(median, right_id);
+        };
+        true
+    }
+
+    /// Adds a value to the tree, reserving the nodes it could take first,
+    /// so the tree is left untouched if the allocation fails.
+    /// Returns whether the value was newly inserted.
+    ///
+    /// The reserved nodes are one for every full node separating on the way up
+    /// and one for a new root, which are at most the height of the tree plus one.
+    pub fn try_insert(&mut self, value: T) -> Result<bool, TryReserveError> {
+        let (leaf_id, idx) = match self.search_by(&|v: &T| self.cmp.compare(v, &value)) {
+            Ok(_) => return Ok(false),
+            Err(position) => position,
+        };
+        let needed = self.nodes_for_insert(leaf_id);
+        self.try_reserve(needed.saturating_sub(self.free.len()))?;
+        self.insert_at(leaf_id, idx, value, None);
+        self.len += 1;
+        Ok(true)
+    }
+
+    /// Returns the number of nodes the arena can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.arena.capacity()
+    }
 
+    /// Reserves room for at least `additional` more nodes than the arena holds,
+    /// including their entries in the free list, so removing values never allocates either.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows `usize`, or aborts on allocation failure like `Vec::reserve`.
+    pub fn reserve(&mut self, additional: usize) {
+        let nodes = self
+            .arena
+            .len()
+            .checked_add(additional)
+            .expect("capacity overflow");
+        self.arena.reserve(additional);
+        self.free.reserve(nodes - self.free.len());
+    }
+
+    /// Tries to reserve room for at least `additional` more nodes than the arena holds,
+    /// like [`Tree::reserve`] but returning an error instead of failing.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let nodes = self
+            .arena
+            .len()
+            .checked_add(additional)
+            .ok_or_else(capacity_overflow)?;
+        self.arena.try_reserve(additional)?;
+        self.free.try_reserve(nodes - self.free.len())
+    }
+
+    /// Compacts the arena and releases the memory it doesn't use anymore.
+    pub fn shrink_to_fit(&mut self) {
+        self.compact();
+        self.arena.shrink_to_fit();
+        self.free.shrink_to_fit();
+    }
+}
+
+/// Builds the error `try_reserve` returns when the node count overflows `usize`.
+///
+/// `TryReserveError` can't be constructed outside of `alloc`, so an empty `Vec` is asked
+/// for more bytes than `isize::MAX`, which fails on the layout check before allocating
+/// and reports the same capacity overflow.
+fn capacity_overflow() -> TryReserveError {
+    Vec::<u8>::new().try_reserve(usize::MAX).unwrap_err()
+}
+
+impl<T, const M: usize, S, C> Tree<T, M, S, C>
+where
+    T: Debug,
+{
     pub fn format_debug(&self) -> String {
         if self.arena.is_empty() {
             return String::from("[]");
         }
-        use std::collections::VecDeque;
+        use alloc::collections::VecDeque;
         let mut q = VecDeque::with_capacity(self.arena.len());
         let mut cur = &self.arena[self.root_id];
 
@@ -694,7 +1298,7 @@ fn insert_3() {
 #[test]
 fn insert_4() {
     let mut t = Tree::<_, 3>::default();
-    for &val in vec![1, 2, 3].iter() {
+    for &val in [1, 2, 3].iter() {
         t.insert(val);
     }
     assert_eq!(t.format_debug(), "#2[2]\n#0[1] #1[3]");
@@ -718,7 +1322,7 @@ fn insert_4() {
 #[test]
 fn insert_5() {
     let mut t = Tree::<_, 4>::default();
-    for &val in vec![1, 2, 3].iter() {
+    for &val in [1, 2, 3].iter() {
         t.insert(val);
     }
     assert_eq!(t.format_debug(), "#0[1, 2, 3]");
@@ -726,7 +1330,7 @@ fn insert_5() {
     t.insert(4);
     assert_eq!(t.format_debug(), "#2[3]\n#0[1, 2] #1[4]");
 
-    for &val in vec![5, 6].iter() {
+    for &val in [5, 6].iter() {
         t.insert(val);
     }
     assert_eq!(t.format_debug(), "#2[3]\n#0[1, 2] #1[4, 5, 6]");
@@ -734,7 +1338,7 @@ fn insert_5() {
     t.insert(7);
     assert_eq!(t.format_debug(), "#2[3, 6]\n#0[1, 2] #1[4, 5] #3[7]");
 
-    for &val in vec![8, 9].iter() {
+    for &val in [8, 9].iter() {
         t.insert(val);
     }
     assert_eq!(t.format_debug(), "#2[3, 6]\n#0[1, 2] #1[4, 5] #3[7, 8, 9]");
@@ -745,7 +1349,7 @@ fn insert_5() {
         "#2[3, 6, 9]\n#0[1, 2] #1[4, 5] #3[7, 8] #4[10]"
     );
 
-    for &val in vec![11, 12].iter() {
+    for &val in [11, 12].iter() {
         t.insert(val);
     }
     assert_eq!(
@@ -766,7 +1370,7 @@ fn traversal_bfs() {
         t.insert(val);
     }
 
-    assert_eq!(t.traversal_bfs(), vec![4, 2, 6, 1, 3, 5, 7]);
+    assert_eq!(t.traversal_bfs(), vec![&4, &2, &6, &1, &3, &5, &7]);
 }
 
 #[test]
@@ -824,7 +1428,7 @@ fn delete_notfound() {
     for val in 1..4 {
         t.insert(val);
     }
-    assert_eq!(t.delete(4).is_some(), false);
+    assert!(t.delete(&4).is_none());
 }
 
 #[test]
@@ -843,7 +1447,7 @@ fn delete_1() {
 #0[1] #1[3] #3[6, 7]"
     );
 
-    t.delete(6);
+    t.delete(&6);
 
     assert_eq!(
         t.format_debug(),
@@ -868,7 +1472,7 @@ fn delete_2() {
 #0[1] #1[3] #3[6, 7]"
     );
 
-    t.delete(4);
+    t.delete(&4);
 
     assert_eq!(
         t.format_debug(),
@@ -893,7 +1497,7 @@ fn delete_3() {
 #0[1] #1[3] #3[6, 7]"
     );
 
-    t.delete(3);
+    t.delete(&3);
 
     assert_eq!(
         t.format_debug(),
@@ -918,7 +1522,7 @@ fn delete_4() {
 #0[1] #1[3] #3[6, 7]"
     );
 
-    t.delete(1);
+    t.delete(&1);
 
     assert_eq!(
         t.format_debug(),
@@ -941,7 +1545,7 @@ fn delete_5() {
 #0[1] #1[3] #3[6]"
     );
 
-    t.delete(3);
+    t.delete(&3);
 
     assert_eq!(
         t.format_debug(),
@@ -965,7 +1569,7 @@ fn delete_6() {
 #0[1] #1[3] #3[5] #4[7]"
     );
 
-    t.delete(7);
+    t.delete(&7);
 
     assert_eq!(
         t.format_debug(),
@@ -987,13 +1591,13 @@ fn delete_7() {
 #0[1] #1[3]"
     );
 
-    t.delete(2);
+    t.delete(&2);
     assert_eq!(t.format_debug(), "#0[1, 3]");
 
-    t.delete(1);
+    t.delete(&1);
     assert_eq!(t.format_debug(), "#0[3]");
 
-    t.delete(3);
+    t.delete(&3);
     assert_eq!(t.format_debug(), "#0[]");
 }
 
@@ -1009,7 +1613,7 @@ fn delete_8() {
         "#2[3]
 #0[1, 2] #1[4]"
     );
-    t.delete(4);
+    t.delete(&4);
 
     assert_eq!(
         t.format_debug(),
@@ -1032,7 +1636,7 @@ fn delete_9_rotate_left() {
 #0[1] #1[3] #3[5] #4[7] #7[9]"
     );
 
-    t.delete(2);
+    t.delete(&2);
 
     assert_eq!(
         t.format_debug(),
@@ -1064,7 +1668,7 @@ fn delete_10_rotate_right() {
 #0[1] #7[3] #4[5] #3[7] #1[9]"
     );
 
-    t.delete(8);
+    t.delete(&8);
 
     assert_eq!(
         t.format_debug(),
@@ -1080,6 +1684,224 @@ fn delete_10_rotate_right() {
     assert_eq!(node6.children.len(), 2);
 }
 
+#[test]
+fn delete_12_rotate_right_keeps_order() {
+    let mut t = Tree::<_, 5>::default();
+    for val in (1..30).rev() {
+        t.insert(val);
+    }
+    for val in (1..30).filter(|v| v % 3 == 0) {
+        t.delete(&val);
+    }
+
+    let vals: Vec<_> = t.range(..).copied().collect();
+    let expected: Vec<_> = (1..30).filter(|v| v % 3 != 0).collect();
+    assert_eq!(vals, expected);
+}
+
+#[test]
+fn len() {
+    let mut t = Tree::<_, 3>::default();
+    assert!(t.is_empty());
+    assert_eq!(t.len(), 0);
+
+    for val in 0..10 {
+        assert!(t.insert(val));
+    }
+    assert!(!t.insert(5));
+    assert_eq!(t.len(), 10);
+
+    assert_eq!(t.delete(&5), Some(5));
+    assert_eq!(t.delete(&5), None);
+    assert_eq!(t.len(), 9);
+
+    for val in 0..10 {
+        t.delete(&val);
+    }
+    assert!(t.is_empty());
+}
+
+#[test]
+fn first_last() {
+    let mut t = Tree::<_, 3>::default();
+    assert_eq!(t.first(), None);
+    assert_eq!(t.last(), None);
+    assert_eq!(t.pop_first(), None);
+    assert_eq!(t.pop_last(), None);
+
+    for val in (0..100).map(|v| v * 7919 % 100) {
+        t.insert(val);
+    }
+    assert_eq!(t.first(), Some(&0));
+    assert_eq!(t.last(), Some(&99));
+
+    for val in 0..50 {
+        assert_eq!(t.pop_first(), Some(val));
+        assert_eq!(t.pop_last(), Some(99 - val));
+        assert_eq!(t.len(), 98 - val * 2);
+        assert_eq!(t.validate(), Ok(()));
+    }
+    assert!(t.is_empty());
+    assert_eq!(t.pop_first(), None);
+}
+
+#[test]
+fn reuse_freed_nodes() {
+    let mut t = Tree::<_, 4>::default();
+    for val in 0..1000 {
+        t.insert(val);
+    }
+    let arena_len = t.arena.len();
+
+    for _ in 0..10 {
+        for val in 0..1000 {
+            t.delete(&val);
+        }
+        assert_eq!(t.free.len(), t.arena.len() - 1);
+        assert!(!t.free.contains(&t.root_id));
+
+        for val in 0..1000 {
+            t.insert(val);
+        }
+        assert_eq!(t.arena.len(), arena_len);
+    }
+    assert_eq!(
+        t.iter().copied().collect::<Vec<_>>(),
+        (0..1000).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn compact() {
+    let mut t = Tree::<_, 3>::default();
+    for val in 1..10 {
+        t.insert(val);
+    }
+    t.delete(&2);
+    assert_eq!(
+        t.format_debug(),
+        "#6[6]
+#2[4] #5[8]
+#0[1, 3] #3[5] #4[7] #7[9]"
+    );
+
+    t.compact();
+    assert_eq!(
+        t.format_debug(),
+        "#0[6]
+#1[4] #2[8]
+#3[1, 3] #4[5] #5[7] #6[9]"
+    );
+    assert_eq!(t.arena.len(), 7);
+    for node in t.arena.iter() {
+        for &child_id in node.children.iter() {
+            assert_eq!(t.arena[child_id].parent, Some(node.idx));
+        }
+    }
+}
+
+#[test]
+fn shrink_to_fit() {
+    let mut t = Tree::<_, 4>::default();
+    for val in 0..1000 {
+        t.insert(val);
+    }
+    for val in (0..1000).filter(|v| v % 10 != 0) {
+        t.delete(&val);
+    }
+    let arena_len = t.arena.len();
+
+    t.shrink_to_fit();
+    assert!(t.free.is_empty());
+    assert!(t.arena.len() < arena_len);
+    assert_eq!(t.arena.capacity(), t.arena.len());
+    assert_eq!(t.root_id, 0);
+    assert_eq!(
+        t.iter().copied().collect::<Vec<_>>(),
+        (0..1000).step_by(10).collect::<Vec<_>>()
+    );
+
+    for val in 0..1000 {
+        t.insert(val);
+    }
+    for val in (0..1000).filter(|v| v % 2 == 0) {
+        t.delete(&val);
+    }
+    assert_eq!(
+        t.iter().copied().collect::<Vec<_>>(),
+        (1..1000).step_by(2).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn reserve() {
+    let mut t = Tree::<_, 4>::with_capacity(100);
+    assert!(t.capacity() >= 100);
+    let arena_ptr = t.arena.as_ptr();
+    let mut val = 0;
+    while t.arena.len() < 90 {
+        assert_eq!(t.try_insert(val), Ok(true));
+        val += 1;
+    }
+    assert_eq!(t.arena.as_ptr(), arena_ptr);
+    assert_eq!(t.try_insert(0), Ok(false));
+
+    t.reserve(50);
+    assert!(t.capacity() >= t.arena.len() + 50);
+    assert!(t.try_reserve(usize::MAX).is_err());
+    for val in 0..1000 {
+        t.try_insert(val).unwrap();
+    }
+    assert_eq!(t.len(), 1000);
+    assert_eq!(t.validate(), Ok(()));
+}
+
+#[test]
+fn try_reserve_overflow() {
+    let mut t = Tree::<_, 4>::with_capacity(10);
+    for val in 0..20 {
+        t.insert(val);
+    }
+    let capacity = t.capacity();
+    // the node count `arena.len() + additional` overflows by one, which is an error, not a panic
+    let err = t.try_reserve(usize::MAX - t.arena.len() + 1).unwrap_err();
+    assert!(err.to_string().contains("capacity"));
+    assert_eq!(t.capacity(), capacity);
+    assert_eq!(t.validate(), Ok(()));
+}
+
+#[test]
+#[should_panic(expected = "capacity overflow")]
+fn reserve_overflow() {
+    let mut t = Tree::<_, 4>::default();
+    t.insert(1);
+    t.reserve(usize::MAX);
+}
+
+#[test]
+fn non_copy_values() {
+    use core::ops::Bound;
+
+    let mut t = Tree::<String, 3>::default();
+    for val in ["d", "b", "a", "c", "e", "f"] {
+        t.insert(val.to_string());
+    }
+
+    assert_eq!(t.get("c").map(String::as_str), Some("c"));
+    assert_eq!(t.get("g"), None);
+    assert_eq!(t.delete("b"), Some("b".to_string()));
+    assert_eq!(t.delete("b"), None);
+
+    let vals: Vec<_> = t
+        .range::<str, _>((Bound::Included("b"), Bound::Excluded("e")))
+        .map(String::as_str)
+        .collect();
+    assert_eq!(vals, vec!["c", "d"]);
+
+    let vals: Vec<_> = t.into_iter().collect();
+    assert_eq!(vals, vec!["a", "c", "d", "e", "f"]);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1092,7 +1914,7 @@ mod tests {
         let mut rng = Pcg64::seed_from_u64(seed as u64);
         let mut vec: Vec<_> = (0..n).collect();
         vec.shuffle(&mut rng);
-        return vec;
+        vec
     }
 
     #[test]
@@ -1106,12 +1928,29 @@ mod tests {
 
         let vals = rand_vec(20, 3);
         for val in vals {
-            t.delete(val);
+            t.delete(&val);
         }
 
         assert_eq!(t.format_debug(), "#0[]");
     }
 
+    #[test]
+    fn rand_insert_delete_validate() {
+        let mut t = Tree::<_, 6>::default();
+        for val in rand_vec(2000, 4) {
+            t.insert(val);
+        }
+        assert_eq!(t.validate(), Ok(()));
+
+        for (idx, val) in rand_vec(2000, 5).into_iter().enumerate() {
+            t.delete(&val);
+            if idx % 100 == 0 {
+                assert_eq!(t.validate(), Ok(()));
+            }
+        }
+        assert_eq!(t.validate(), Ok(()));
+    }
+
     #[test]
     fn huge_insert_delete() {
         use std::time::Instant;
@@ -1121,13 +1960,13 @@ mod tests {
         let mut t = Tree::<_, 256>::default();
         let now = Instant::now();
         for &val in vals.iter() {
-            t.insert(val)
+            t.insert(val);
         }
         println!("{}", now.elapsed().as_millis());
 
         let now = Instant::now();
         for &val in vals.iter() {
-            t.delete(val);
+            t.delete(&val);
         }
         println!("{}", now.elapsed().as_millis());
     }
@@ -1141,7 +1980,7 @@ mod tests {
         let mut t = Tree::<_, 256>::default();
         let now = Instant::now();
         for &val in vals.iter() {
-            t.insert(val)
+            t.insert(val);
         }
         println!("{}", now.elapsed().as_millis());
     }
@@ -1155,7 +1994,7 @@ mod tests {
         let mut t = Tree::<_, 256>::default();
         let now = Instant::now();
         for &val in vals.iter() {
-            t.insert(val)
+            t.insert(val);
         }
         println!("{}", now.elapsed().as_millis());
     }
@@ -1169,18 +2008,18 @@ mod tests {
         let mut t = Tree::<_, 256>::default();
         let now = Instant::now();
         for &val in vals.iter() {
-            t.insert(val)
+            t.insert(val);
         }
         println!("{}", now.elapsed().as_millis());
 
         let vals = rand_vec(1_000_000, 1);
         let now = Instant::now();
         for &val in vals.iter() {
-            assert_eq!(t.get(val).is_some(), true);
+            assert!(t.get(&val).is_some());
         }
         println!("{}", now.elapsed().as_millis());
 
-        assert_eq!(t.get(1_000_001).is_none(), true);
+        assert!(t.get(&1_000_001).is_none());
     }
 }
 
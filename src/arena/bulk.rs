@@ -0,0 +1,283 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::iter::FromIterator;
+
+#[cfg(test)]
+use super::Size;
+use super::{Compare, Summary, Tree};
+
+impl<T, const M: usize, S, C> Tree<T, M, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    /// Builds a tree from values in strictly ascending order in O(n),
+    /// filling every node up to its capacity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the values are not sorted or contain duplicates.
+    pub fn from_sorted_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        C: Default,
+    {
+        Self::from_sorted_iter_with_fill(iter, M - 1)
+    }
+
+    /// Builds a tree from values in strictly ascending order in O(n),
+    /// putting `fill` values into every node except the last one of each level.
+    ///
+    /// `fill` is clamped between the minimum and maximum number of values a node can hold.
+    /// A lower fill leaves room for later insertions without splitting.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the values are not sorted or contain duplicates.
+    pub fn from_sorted_iter_with_fill<I>(iter: I, fill: usize) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        C: Default,
+    {
+        let mut t = Self::default();
+        t.load_sorted(iter, fill);
+        t
+    }
+
+    /// Builds the empty tree up from values in strictly ascending order.
+    pub(super) fn load_sorted<I>(&mut self, iter: I, fill: usize)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        if self.try_load_sorted(iter, fill).is_err() {
+            panic!("values must be sorted and unique");
+        }
+    }
+
+    /// Builds the empty tree up from values in strictly ascending order,
+    /// or returns the index of the first value not greater than the one before it.
+    /// The tree is left half-built on error and has to be dropped.
+    pub(super) fn try_load_sorted<I>(&mut self, iter: I, fill: usize) -> Result<(), usize>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let fill = fill.clamp(Self::min_values().max(1), M - 1);
+
+        // the empty root becomes the first leaf
+        let mut len = 0;
+        let values = iter
+            .into_iter()
+            .inspect(|_| len += 1)
+            .map(|value| (value, None));
+        let level = self.build_level(self.root_id, None, values, fill);
+        let (mut nodes, mut separators) = level.map_err(|()| len - 1)?;
+        self.len = len;
+
+        while nodes.len() > 1 {
+            let first_node_id = self.new_node();
+            let mut children = nodes.into_iter().map(Some);
+            let first_child = children.next().unwrap();
+            let items = separators.into_iter().zip(children);
+            // the separators are sorted if the values were
+            (nodes, separators) = self
+                .build_level(first_node_id, first_child, items, fill)
+                .unwrap();
+        }
+        self.root_id = nodes[0];
+        self.arena[self.root_id].parent = None;
+        Ok(())
+    }
+
+    fn min_values() -> usize {
+        (M - 1) / 2
+    }
+
+    /// Builds one level of the tree from left to right.
+    ///
+    /// Items are values for the leaf level, or separators with their right child
+    /// for the upper levels. Returns the nodes and the separators between them,
+    /// which are going to be put into the level above.
+    /// Fails if an item is not greater than the one before it.
+    fn build_level<I>(
+        &mut self,
+        first_node_id: usize,
+        first_child: Option<usize>,
+        items: I,
+        fill: usize,
+    ) -> Result<(Vec<usize>, Vec<T>), ()>
+    where
+        I: Iterator<Item = (T, Option<usize>)>,
+    {
+        let mut nodes = vec![first_node_id];
+        let mut separators: Vec<T> = Vec::new();
+        if let Some(child_id) = first_child {
+            self.arena[first_node_id].children.push(child_id);
+        }
+
+        for (value, child) in items {
+            let cur_id = *nodes.last().unwrap();
+            let cur = &mut self.arena[cur_id];
+            let prev = cur.values.last().or_else(|| separators.last());
+            let cmp = &self.cmp;
+            if prev.is_some_and(|prev| cmp.compare(prev, &value) != Ordering::Less) {
+                return Err(());
+            }
+
+            if cur.values.len() < fill {
+                cur.values.push(value);
+                cur.children.extend(child);
+            } else {
+                // the value goes to the level above, and a new node begins
+                separators.push(value);
+                let node_id = self.new_node();
+                self.arena[node_id].children.extend(child);
+                nodes.push(node_id);
+            }
+        }
+
+        self.fix_last_node(&mut nodes, &mut separators);
+
+        for &node_id in nodes.iter() {
+            let node = &self.arena[node_id];
+            for idx in 0..node.children.len() {
+                let child_id = self.arena[node_id].children[idx];
+                self.arena[child_id].parent = Some(node_id);
+            }
+            self.update_summary(node_id);
+        }
+        Ok((nodes, separators))
+    }
+
+    /// Refills the last node of the level from its left sibling if it's deficient,
+    /// or merges them if there are not enough values for two nodes.
+    fn fix_last_node(&mut self, nodes: &mut Vec<usize>, separators: &mut Vec<T>) {
+        let len = nodes.len();
+        if len < 2 || self.arena[nodes[len - 1]].values.len() >= Self::min_values() {
+            return;
+        }
+        let (left_id, right_id) = (nodes[len - 2], nodes[len - 1]);
+
+        // values: | left | separator | right |
+        let mut values: Vec<T> = self.arena[left_id].values.drain(..).collect();
+        values.push(separators.pop().unwrap());
+        values.extend(self.arena[right_id].values.drain(..));
+        let mut children: Vec<usize> = self.arena[left_id].children.drain(..).collect();
+        children.extend(self.arena[right_id].children.drain(..));
+
+        if values.len() - 1 < 2 * Self::min_values() {
+            let left = &mut self.arena[left_id];
+            left.values.extend(values);
+            left.children.extend(children);
+            nodes.pop();
+            self.free_node(right_id);
+            return;
+        }
+
+        let half = (values.len() - 1) / 2;
+        let right = &mut self.arena[right_id];
+        right.values.extend(values.drain(half + 1..));
+        if !children.is_empty() {
+            right.children.extend(children.drain(half + 1..));
+        }
+        separators.push(values.pop().unwrap());
+        let left = &mut self.arena[left_id];
+        left.values.extend(values);
+        left.children.extend(children);
+    }
+}
+
+impl<T, const M: usize, S, C> FromIterator<T> for Tree<T, M, S, C>
+where
+    C: Compare<T> + Default,
+    S: Summary<T>,
+{
+    /// Sorts and deduplicates the values, then builds the tree bottom-up.
+    /// The first one of equal values is kept, like inserting them in order.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut t = Self::default();
+        let mut values: Vec<T> = iter.into_iter().collect();
+        values.sort_by(|a, b| t.cmp.compare(a, b));
+        values.dedup_by(|a, b| t.cmp.compare(a, b) == Ordering::Equal);
+        t.load_sorted(values, M - 1);
+        t
+    }
+}
+
+#[test]
+fn from_sorted_iter() {
+    for n in 0..200 {
+        let t = Tree::<_, 3>::from_sorted_iter(0..n);
+        assert_eq!(t.validate(), Ok(()));
+        assert_eq!(t.len(), n);
+        assert_eq!(
+            t.iter().copied().collect::<Vec<_>>(),
+            (0..n).collect::<Vec<_>>()
+        );
+
+        let t = Tree::<_, 6>::from_sorted_iter(0..n);
+        assert_eq!(t.validate(), Ok(()));
+        assert_eq!(t.len(), n);
+        assert_eq!(
+            t.iter().copied().collect::<Vec<_>>(),
+            (0..n).collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn from_sorted_iter_layout() {
+    let t = Tree::<_, 3>::from_sorted_iter(1..8);
+    assert_eq!(
+        t.format_debug(),
+        "#3[3, 6]
+#0[1, 2] #1[4, 5] #2[7]"
+    );
+
+    // the last leaf takes values from its sibling
+    let t = Tree::<_, 5>::from_sorted_iter(1..7);
+    assert_eq!(
+        t.format_debug(),
+        "#2[3]
+#0[1, 2] #1[4, 5, 6]"
+    );
+}
+
+#[test]
+fn from_sorted_iter_with_fill() {
+    for fill in 0..10 {
+        for n in [0, 1, 10, 100, 1000] {
+            let mut t = Tree::<_, 8, Size>::from_sorted_iter_with_fill(0..n, fill);
+            assert_eq!(t.validate(), Ok(()));
+            assert_eq!(t.len(), n);
+            assert_eq!(t.select(n / 2), (0..n).nth(n / 2).as_ref());
+
+            for val in (0..n).filter(|v| v % 3 == 0) {
+                t.delete(&val);
+            }
+            for val in n..n + 10 {
+                t.insert(val);
+            }
+            assert_eq!(t.validate(), Ok(()));
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "values must be sorted and unique")]
+fn from_sorted_iter_unsorted() {
+    Tree::<_, 3>::from_sorted_iter([1, 2, 4, 3]);
+}
+
+#[test]
+#[should_panic(expected = "values must be sorted and unique")]
+fn from_sorted_iter_duplicated() {
+    Tree::<_, 3>::from_sorted_iter([1, 2, 3, 3]);
+}
+
+#[test]
+fn from_iter() {
+    let t: Tree<_, 4> = [5, 3, 9, 1, 3, 7, 5].iter().copied().collect();
+    assert_eq!(t.validate(), Ok(()));
+    assert_eq!(t.iter().copied().collect::<Vec<_>>(), vec![1, 3, 5, 7, 9]);
+}
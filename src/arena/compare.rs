@@ -0,0 +1,119 @@
+use core::cmp::Ordering;
+
+/// A comparator which decides the order of the values in a tree.
+///
+/// The tree keeps an instance of it, so the ordering can depend on runtime state.
+/// Lookups with a borrowed form `Q` of the values compare through `Compare<Q>`.
+pub trait Compare<T: ?Sized> {
+    fn compare(&self, a: &T, b: &T) -> Ordering;
+}
+
+/// The comparator ordering values by their `Ord` implementation.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Natural;
+
+impl<T> Compare<T> for Natural
+where
+    T: Ord + ?Sized,
+{
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+impl<T, F> Compare<T> for F
+where
+    T: ?Sized,
+    F: Fn(&T, &T) -> Ordering,
+{
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        self(a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arena::Tree;
+
+    /// Orders strings ignoring ASCII case.
+    #[derive(Debug, Default, Clone)]
+    struct CaseInsensitive;
+
+    impl Compare<str> for CaseInsensitive {
+        fn compare(&self, a: &str, b: &str) -> Ordering {
+            a.bytes()
+                .map(|c| c.to_ascii_lowercase())
+                .cmp(b.bytes().map(|c| c.to_ascii_lowercase()))
+        }
+    }
+
+    impl Compare<String> for CaseInsensitive {
+        fn compare(&self, a: &String, b: &String) -> Ordering {
+            Compare::<str>::compare(self, a, b)
+        }
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let mut t = Tree::<String, 3, (), CaseInsensitive>::default();
+        for word in "the Quick brown fox jumps over THE lazy dog".split(' ') {
+            t.insert(word.to_string());
+        }
+        assert_eq!(t.len(), 8);
+        assert_eq!(t.get("QUICK").map(String::as_str), Some("Quick"));
+        assert_eq!(t.get("the").map(String::as_str), Some("the"));
+        assert_eq!(
+            t.iter().map(String::as_str).collect::<Vec<_>>(),
+            ["brown", "dog", "fox", "jumps", "lazy", "over", "Quick", "the"]
+        );
+        assert_eq!(t.delete("Fox"), Some("fox".to_string()));
+        assert_eq!(t.validate(), Ok(()));
+    }
+
+    #[test]
+    fn runtime_comparator() {
+        // the order depends on the distance to a point known at runtime
+        let center = 500;
+        let by_distance = move |a: &i32, b: &i32| {
+            let key = |v: &i32| ((v - center).abs(), *v);
+            key(a).cmp(&key(b))
+        };
+        let mut t = Tree::<_, 4, (), _>::with_comparator(by_distance);
+        for val in (0..1000).map(|v| v * 7919 % 1000) {
+            t.insert(val);
+        }
+        assert_eq!(t.validate(), Ok(()));
+        assert_eq!(t.first(), Some(&500));
+        assert_eq!(
+            t.iter().take(5).copied().collect::<Vec<_>>(),
+            [500, 499, 501, 498, 502]
+        );
+        assert_eq!(
+            t.range(&499..&497).copied().collect::<Vec<_>>(),
+            [499, 501, 498, 502]
+        );
+
+        let mut other = t.split_off(&0);
+        assert_eq!(other.first(), Some(&0));
+        assert_eq!(t.len() + other.len(), 1000);
+        t.append(&mut other);
+        assert_eq!(t.len(), 1000);
+        assert_eq!(t.validate(), Ok(()));
+    }
+
+    #[test]
+    fn reverse_order() {
+        let mut t = Tree::<_, 3, (), _>::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+        for val in 0..100 {
+            t.insert(val);
+        }
+        assert_eq!(t.first(), Some(&99));
+        assert_eq!(t.pop_last(), Some(0));
+        assert_eq!(
+            t.range(&20..=&10).copied().collect::<Vec<_>>(),
+            (10..=20).rev().collect::<Vec<_>>()
+        );
+        assert_eq!(t.validate(), Ok(()));
+    }
+}
@@ -0,0 +1,379 @@
+use core::borrow::Borrow;
+use core::ops::Bound;
+
+use super::{Compare, Natural, Summary, Tree};
+
+/// A cursor pointing at a value of a `Tree`, which can move in both directions.
+///
+/// Besides the values, there is a ghost position lying after the last value
+/// and before the first one, where the cursor points at nothing.
+///
+/// Created by [`Tree::lower_bound`] or [`Tree::upper_bound`].
+#[derive(Debug)]
+pub struct Cursor<'a, T, const M: usize, S = (), C = Natural> {
+    tree: &'a Tree<T, M, S, C>,
+    handle: Option<(usize, usize)>,
+}
+
+impl<'a, T, const M: usize, S, C> Clone for Cursor<'a, T, M, S, C> {
+    fn clone(&self) -> Self {
+        Cursor {
+            tree: self.tree,
+            handle: self.handle,
+        }
+    }
+}
+
+impl<'a, T, const M: usize, S, C> Cursor<'a, T, M, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    /// Returns the value the cursor points at, or `None` at the ghost position.
+    pub fn current(&self) -> Option<&'a T> {
+        self.handle.map(|handle| self.tree.value(handle))
+    }
+
+    /// Moves to the next value, or from the last value to the ghost position.
+    pub fn move_next(&mut self) {
+        self.handle = self.tree.cursor_next(self.handle);
+    }
+
+    /// Moves to the previous value, or from the first value to the ghost position.
+    pub fn move_prev(&mut self) {
+        self.handle = self.tree.cursor_prev(self.handle);
+    }
+
+    /// Returns the value `move_next` would move to.
+    pub fn peek_next(&self) -> Option<&'a T> {
+        let handle = self.tree.cursor_next(self.handle)?;
+        Some(self.tree.value(handle))
+    }
+
+    /// Returns the value `move_prev` would move to.
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        let handle = self.tree.cursor_prev(self.handle)?;
+        Some(self.tree.value(handle))
+    }
+}
+
+/// A cursor like [`Cursor`], which can also remove and insert values around it.
+///
+/// The tree is rebalanced after every modification, which moves values between nodes,
+/// so the cursor finds its position again from the value removed or inserted.
+///
+/// Created by [`Tree::lower_bound_mut`] or [`Tree::upper_bound_mut`].
+#[derive(Debug)]
+pub struct CursorMut<'a, T, const M: usize, S = (), C = Natural> {
+    tree: &'a mut Tree<T, M, S, C>,
+    handle: Option<(usize, usize)>,
+}
+
+impl<'a, T, const M: usize, S, C> CursorMut<'a, T, M, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    /// Returns the value the cursor points at, or `None` at the ghost position.
+    pub fn current(&self) -> Option<&T> {
+        self.handle.map(|handle| self.tree.value(handle))
+    }
+
+    /// Moves to the next value, or from the last value to the ghost position.
+    pub fn move_next(&mut self) {
+        self.handle = self.tree.cursor_next(self.handle);
+    }
+
+    /// Moves to the previous value, or from the first value to the ghost position.
+    pub fn move_prev(&mut self) {
+        self.handle = self.tree.cursor_prev(self.handle);
+    }
+
+    /// Returns the value `move_next` would move to.
+    pub fn peek_next(&self) -> Option<&T> {
+        let handle = self.tree.cursor_next(self.handle)?;
+        Some(self.tree.value(handle))
+    }
+
+    /// Returns the value `move_prev` would move to.
+    pub fn peek_prev(&self) -> Option<&T> {
+        let handle = self.tree.cursor_prev(self.handle)?;
+        Some(self.tree.value(handle))
+    }
+
+    /// Returns a read-only cursor pointing at the same position.
+    pub fn as_cursor(&self) -> Cursor<'_, T, M, S, C> {
+        Cursor {
+            tree: self.tree,
+            handle: self.handle,
+        }
+    }
+
+    /// Removes the value the cursor points at, and moves to the next one.
+    /// Returns `None` at the ghost position.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let handle = self.handle?;
+        let value = self.tree.remove_handle(handle);
+        self.handle = self.tree.lower_handle::<T>(Bound::Excluded(&value));
+        Some(value)
+    }
+
+    /// Inserts the value right before the cursor, or as the last value at the ghost position.
+    /// The cursor keeps pointing at the same value.
+    ///
+    /// Gives the value back if it doesn't lie between the previous value and the current one.
+    pub fn insert_before(&mut self, value: T) -> Result<(), T> {
+        let less = |a: &T, b: &T| self.tree.less(a, b);
+        let fits = self.peek_prev().is_none_or(|prev| less(prev, &value))
+            && self.current().is_none_or(|cur| less(&value, cur));
+        if !fits {
+            return Err(value);
+        }
+
+        let tree = &mut *self.tree;
+        let (leaf_id, idx) = match self.handle {
+            Some((node_id, idx)) if tree.arena[node_id].is_leaf() => (node_id, idx),
+            // after the predecessor, which is the rightmost value in the left child
+            Some((node_id, idx)) => {
+                let (leaf_id, _) = tree.most_right(tree.arena[node_id].children[idx]);
+                (leaf_id, tree.arena[leaf_id].values.len())
+            }
+            None => {
+                let (leaf_id, _) = tree.most_right(tree.root_id);
+                (leaf_id, tree.arena[leaf_id].values.len())
+            }
+        };
+        let inserted = tree.insert_at(leaf_id, idx, value, None);
+        tree.len += 1;
+        if self.handle.is_some() {
+            self.handle = tree.next_handle(inserted);
+        }
+        Ok(())
+    }
+
+    /// Inserts the value right after the cursor, or as the first value at the ghost position.
+    /// The cursor keeps pointing at the same value.
+    ///
+    /// Gives the value back if it doesn't lie between the current value and the next one.
+    pub fn insert_after(&mut self, value: T) -> Result<(), T> {
+        let less = |a: &T, b: &T| self.tree.less(a, b);
+        let fits = self.current().is_none_or(|cur| less(cur, &value))
+            && self.peek_next().is_none_or(|next| less(&value, next));
+        if !fits {
+            return Err(value);
+        }
+
+        let tree = &mut *self.tree;
+        let (leaf_id, idx) = match self.handle {
+            Some((node_id, idx)) if tree.arena[node_id].is_leaf() => (node_id, idx + 1),
+            // before the successor, which is the leftmost value in the right child
+            Some((node_id, idx)) => {
+                let (leaf_id, _) = tree.most_left(tree.arena[node_id].children[idx + 1]);
+                (leaf_id, 0)
+            }
+            None => (tree.most_left(tree.root_id).0, 0),
+        };
+        let inserted = tree.insert_at(leaf_id, idx, value, None);
+        tree.len += 1;
+        if self.handle.is_some() {
+            self.handle = tree.prev_handle(inserted);
+        }
+        Ok(())
+    }
+}
+
+impl<T, const M: usize, S, C> Tree<T, M, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    /// Returns a cursor pointing at the smallest value lying after the bound,
+    /// or at the ghost position if there is none.
+    pub fn lower_bound<K>(&self, bound: Bound<&K>) -> Cursor<'_, T, M, S, C>
+    where
+        T: Borrow<K>,
+        C: Compare<K>,
+        K: ?Sized,
+    {
+        Cursor {
+            handle: self.lower_handle(bound),
+            tree: self,
+        }
+    }
+
+    /// Returns a cursor pointing at the largest value lying before the bound,
+    /// or at the ghost position if there is none.
+    pub fn upper_bound<K>(&self, bound: Bound<&K>) -> Cursor<'_, T, M, S, C>
+    where
+        T: Borrow<K>,
+        C: Compare<K>,
+        K: ?Sized,
+    {
+        Cursor {
+            handle: self.upper_handle(bound),
+            tree: self,
+        }
+    }
+
+    /// Returns a mutable cursor pointing at the smallest value lying after the bound,
+    /// or at the ghost position if there is none.
+    pub fn lower_bound_mut<K>(&mut self, bound: Bound<&K>) -> CursorMut<'_, T, M, S, C>
+    where
+        T: Borrow<K>,
+        C: Compare<K>,
+        K: ?Sized,
+    {
+        CursorMut {
+            handle: self.lower_handle(bound),
+            tree: self,
+        }
+    }
+
+    /// Returns a mutable cursor pointing at the largest value lying before the bound,
+    /// or at the ghost position if there is none.
+    pub fn upper_bound_mut<K>(&mut self, bound: Bound<&K>) -> CursorMut<'_, T, M, S, C>
+    where
+        T: Borrow<K>,
+        C: Compare<K>,
+        K: ?Sized,
+    {
+        CursorMut {
+            handle: self.upper_handle(bound),
+            tree: self,
+        }
+    }
+
+    fn cursor_next(&self, handle: Option<(usize, usize)>) -> Option<(usize, usize)> {
+        match handle {
+            Some(handle) => self.next_handle(handle),
+            None => self.first_handle(),
+        }
+    }
+
+    fn cursor_prev(&self, handle: Option<(usize, usize)>) -> Option<(usize, usize)> {
+        match handle {
+            Some(handle) => self.prev_handle(handle),
+            None => self.last_handle(),
+        }
+    }
+}
+
+#[test]
+fn cursor_move() {
+    let mut t = Tree::<_, 3>::default();
+    for val in (0..20).map(|v| v * 2) {
+        t.insert(val);
+    }
+
+    let mut cur = t.lower_bound(Bound::Included(&7));
+    assert_eq!(cur.current(), Some(&8));
+    assert_eq!(cur.peek_prev(), Some(&6));
+    assert_eq!(cur.peek_next(), Some(&10));
+    cur.move_prev();
+    cur.move_prev();
+    assert_eq!(cur.current(), Some(&4));
+
+    let mut cur = t.lower_bound(Bound::Excluded(&38));
+    assert_eq!(cur.current(), None);
+    assert_eq!(cur.peek_next(), Some(&0));
+    assert_eq!(cur.peek_prev(), Some(&38));
+    cur.move_next();
+    assert_eq!(cur.current(), Some(&0));
+    cur.move_prev();
+    assert_eq!(cur.current(), None);
+
+    let cur = t.upper_bound(Bound::Excluded(&8));
+    assert_eq!(cur.current(), Some(&6));
+    let cur = t.upper_bound(Bound::Included(&8));
+    assert_eq!(cur.current(), Some(&8));
+    let cur = t.upper_bound::<i32>(Bound::Unbounded);
+    assert_eq!(cur.current(), Some(&38));
+
+    let mut cur = t.lower_bound::<i32>(Bound::Unbounded);
+    let mut vals = Vec::new();
+    while let Some(&val) = cur.current() {
+        vals.push(val);
+        cur.move_next();
+    }
+    assert_eq!(vals, (0..20).map(|v| v * 2).collect::<Vec<_>>());
+}
+
+#[test]
+fn cursor_remove_current() {
+    let mut t = Tree::<_, 4>::default();
+    for val in (0..500).map(|v| v * 7919 % 500) {
+        t.insert(val);
+    }
+
+    let mut cur = t.lower_bound_mut(Bound::Included(&100));
+    for val in 100..400 {
+        assert_eq!(cur.current(), Some(&val));
+        if val % 3 == 0 {
+            assert_eq!(cur.remove_current(), Some(val));
+        } else {
+            cur.move_next();
+        }
+    }
+    assert_eq!(cur.peek_prev(), Some(&398));
+    assert_eq!(t.validate(), Ok(()));
+    assert_eq!(
+        t.iter().copied().collect::<Vec<_>>(),
+        (0..500)
+            .filter(|v| !(100..400).contains(v) || v % 3 != 0)
+            .collect::<Vec<_>>()
+    );
+
+    // removing the predecessor of every multiple of ten
+    let vals: Vec<_> = (10..500)
+        .step_by(10)
+        .filter(|v| t.get(v).is_some())
+        .collect();
+    for val in vals {
+        let mut cur = t.upper_bound_mut(Bound::Excluded(&val));
+        cur.remove_current();
+        assert_eq!(cur.current(), Some(&val));
+    }
+    assert_eq!(t.validate(), Ok(()));
+
+    let mut cur = t.lower_bound_mut::<i32>(Bound::Unbounded);
+    while cur.remove_current().is_some() {}
+    assert!(t.is_empty());
+    assert_eq!(t.validate(), Ok(()));
+}
+
+#[test]
+fn cursor_insert() {
+    let mut t = Tree::<_, 3>::default();
+    let mut cur = t.lower_bound_mut::<i32>(Bound::Unbounded);
+    assert_eq!(cur.insert_before(50), Ok(()));
+    assert_eq!(cur.current(), None);
+    assert_eq!(cur.insert_after(0), Ok(()));
+    assert_eq!(cur.peek_next(), Some(&0));
+
+    let mut cur = t.lower_bound_mut(Bound::Included(&50));
+    for val in (1..50).rev() {
+        assert_eq!(cur.insert_before(val), Ok(()));
+        assert_eq!(cur.current(), Some(&(val + 1)));
+        cur.move_prev();
+    }
+    assert_eq!(cur.insert_before(0), Err(0));
+    assert_eq!(cur.insert_after(1), Err(1));
+
+    let mut cur = t.lower_bound_mut(Bound::Included(&0));
+    for val in 100..150 {
+        assert_eq!(cur.insert_before(val), Err(val));
+    }
+
+    let mut cur = t.lower_bound_mut(Bound::Included(&50));
+    for val in (51..100).rev() {
+        assert_eq!(cur.insert_after(val), Ok(()));
+        assert_eq!(cur.current(), Some(&50));
+    }
+
+    assert_eq!(t.validate(), Ok(()));
+    assert_eq!(t.len(), 100);
+    assert_eq!(
+        t.iter().copied().collect::<Vec<_>>(),
+        (0..100).collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,410 @@
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::ops::{Bound, RangeBounds};
+
+use arrayvec::ArrayVec;
+
+use super::{Compare, IntoIter, Summary, Tree};
+
+/// A lazy iterator removing the values in a range which match a predicate.
+///
+/// The values of a leaf are checked together, and the matching ones are moved out
+/// of it at once, so the leaf is rebalanced a single time after they are all yielded.
+/// A value is only taken out of the tree for good once it's yielded,
+/// the ones not yielded yet go back if the iterator is dropped early,
+/// and a panicking predicate leaves the values of its leaf untouched.
+///
+/// Created by [`Tree::extract_if`].
+pub struct ExtractIf<'a, T, const M: usize, S, C, K, R, F>
+where
+    T: Borrow<K>,
+    C: Compare<T> + Compare<K>,
+    S: Summary<T>,
+    K: ?Sized,
+    R: RangeBounds<K>,
+    F: FnMut(&T) -> bool,
+{
+    tree: &'a mut Tree<T, M, S, C>,
+    range: R,
+    pred: F,
+    /// The position of the next value to check.
+    front: Option<(usize, usize)>,
+    /// How many values from the front on the predicate has kept already.
+    kept: usize,
+    /// The values moved out of a leaf and not yielded yet, in descending order.
+    extracted: ArrayVec<T, M>,
+    /// The leaf the extracted values were moved out of.
+    leaf_id: usize,
+    marker: PhantomData<fn(&K)>,
+}
+
+impl<'a, T, const M: usize, S, C, K, R, F> ExtractIf<'a, T, M, S, C, K, R, F>
+where
+    T: Borrow<K>,
+    C: Compare<T> + Compare<K>,
+    S: Summary<T>,
+    K: ?Sized,
+    R: RangeBounds<K>,
+    F: FnMut(&T) -> bool,
+{
+    /// Tells whether the value lies after the end of the range.
+    fn past_end(cmp: &C, range: &R, value: &T) -> bool {
+        match range.end_bound() {
+            Bound::Included(end) => cmp.compare(value.borrow(), end) == Ordering::Greater,
+            Bound::Excluded(end) => cmp.compare(value.borrow(), end) != Ordering::Less,
+            Bound::Unbounded => false,
+        }
+    }
+
+    /// Checks the values of the leaf from the front on, then moves the matching ones
+    /// into `extracted`. The predicate is called before the leaf is changed.
+    fn extract_from_leaf(&mut self, (leaf_id, start): (usize, usize)) {
+        let mut matched = ArrayVec::<bool, M>::new();
+        let mut ended = false;
+        for value in &self.tree.arena[leaf_id].values[start..] {
+            if Self::past_end(&self.tree.cmp, &self.range, value) {
+                ended = true;
+                break;
+            }
+            if self.kept > 0 {
+                self.kept -= 1;
+                matched.push(false);
+            } else {
+                matched.push((self.pred)(value));
+            }
+        }
+
+        let Some(last) = matched.iter().rposition(|&m| m) else {
+            let end = start + matched.len();
+            self.front = match end.checked_sub(1) {
+                Some(last_idx) if !ended => self.tree.next_handle((leaf_id, last_idx)),
+                _ => None,
+            };
+            return;
+        };
+
+        let leaf = &mut self.tree.arena[leaf_id];
+        for (idx, value) in leaf.values.take().into_iter().enumerate() {
+            if idx >= start && matched.get(idx - start) == Some(&true) {
+                self.extracted.push(value);
+            } else {
+                leaf.values.push(value);
+            }
+        }
+        self.extracted.reverse();
+        self.tree.len -= self.extracted.len();
+        self.tree.update_summaries_up(leaf_id);
+        self.leaf_id = leaf_id;
+        // the values the predicate kept after the last match are found again
+        // once the leaf is rebalanced, without calling it twice
+        self.kept = matched.len() - 1 - last;
+        self.front = if ended { None } else { Some((leaf_id, 0)) };
+    }
+
+    /// Rebalances the leaf the values were extracted from, then searches
+    /// the value after the last extracted one unless the range has ended.
+    fn refill_leaf(&mut self, last: &T) {
+        self.tree.refill(self.leaf_id);
+        if self.front.is_some() {
+            self.front = self.tree.lower_handle::<T>(Bound::Excluded(last));
+        }
+    }
+}
+
+impl<'a, T, const M: usize, S, C, K, R, F> Iterator for ExtractIf<'a, T, M, S, C, K, R, F>
+where
+    T: Borrow<K>,
+    C: Compare<T> + Compare<K>,
+    S: Summary<T>,
+    K: ?Sized,
+    R: RangeBounds<K>,
+    F: FnMut(&T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(value) = self.extracted.pop() {
+                if self.extracted.is_empty() {
+                    self.refill_leaf(&value);
+                }
+                return Some(value);
+            }
+            let handle = self.front?;
+            if self.tree.arena[handle.0].is_leaf() {
+                self.extract_from_leaf(handle);
+                continue;
+            }
+
+            // a separator is removed on its own and replaced by its predecessor
+            let value = self.tree.value(handle);
+            if Self::past_end(&self.tree.cmp, &self.range, value) {
+                self.front = None;
+                return None;
+            }
+            if !(self.pred)(value) {
+                self.front = self.tree.next_handle(handle);
+                continue;
+            }
+            let value = self.tree.remove_handle(handle);
+            self.front = self.tree.lower_handle::<T>(Bound::Excluded(&value));
+            return Some(value);
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let rest = if self.front.is_some() {
+            self.tree.len
+        } else {
+            0
+        };
+        (self.extracted.len(), Some(self.extracted.len() + rest))
+    }
+}
+
+impl<'a, T, const M: usize, S, C, K, R, F> FusedIterator for ExtractIf<'a, T, M, S, C, K, R, F>
+where
+    T: Borrow<K>,
+    C: Compare<T> + Compare<K>,
+    S: Summary<T>,
+    K: ?Sized,
+    R: RangeBounds<K>,
+    F: FnMut(&T) -> bool,
+{
+}
+
+impl<'a, T, const M: usize, S, C, K, R, F> Drop for ExtractIf<'a, T, M, S, C, K, R, F>
+where
+    T: Borrow<K>,
+    C: Compare<T> + Compare<K>,
+    S: Summary<T>,
+    K: ?Sized,
+    R: RangeBounds<K>,
+    F: FnMut(&T) -> bool,
+{
+    /// Puts the extracted values which were not yielded back into their leaf.
+    fn drop(&mut self) {
+        if self.extracted.is_empty() {
+            return;
+        }
+        let tree = &mut *self.tree;
+        tree.len += self.extracted.len();
+        let (leaf, cmp) = (&mut tree.arena[self.leaf_id], &tree.cmp);
+        for value in self.extracted.drain(..) {
+            let cmp = |v: &T| cmp.compare(v, &value);
+            let (idx, _) = Tree::<T, M, S, C>::binary_search_by(&leaf.values, &cmp);
+            leaf.values.insert(idx, value);
+        }
+        tree.update_summaries_up(self.leaf_id);
+        tree.refill(self.leaf_id);
+    }
+}
+
+/// Empties the tree when dropped before the values are loaded,
+/// so a predicate panicking halfway never leaves it half-built.
+struct Rebuild<'a, T, const M: usize, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    tree: &'a mut Tree<T, M, S, C>,
+    loaded: bool,
+}
+
+impl<'a, T, const M: usize, S, C> Drop for Rebuild<'a, T, M, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    fn drop(&mut self) {
+        if !self.loaded {
+            self.tree.reset();
+        }
+    }
+}
+
+impl<T, const M: usize, S, C> Tree<T, M, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    /// Drops every node and leaves a single empty root.
+    fn reset(&mut self) {
+        self.arena.clear();
+        self.free.clear();
+        self.len = 0;
+        self.root_id = self.new_node();
+    }
+
+    /// Keeps only the values for which the predicate returns `true`.
+    ///
+    /// The values are visited in ascending order once, and the tree is rebuilt
+    /// from the kept ones in O(n). If the predicate panics, the tree is left empty.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let arena = core::mem::take(&mut self.arena);
+        let values = IntoIter::new(arena, self.root_id, self.len);
+        self.reset();
+
+        let mut rebuild = Rebuild {
+            tree: self,
+            loaded: false,
+        };
+        rebuild.tree.load_sorted(values.filter(|v| f(v)), M - 1);
+        rebuild.loaded = true;
+    }
+
+    /// Creates an iterator which visits the values in the range in ascending order,
+    /// and removes the ones for which the predicate returns `true`.
+    pub fn extract_if<K, R, F>(&mut self, range: R, pred: F) -> ExtractIf<'_, T, M, S, C, K, R, F>
+    where
+        T: Borrow<K>,
+        C: Compare<K>,
+        K: ?Sized,
+        R: RangeBounds<K>,
+        F: FnMut(&T) -> bool,
+    {
+        let front = self.lower_handle(range.start_bound());
+        ExtractIf {
+            tree: self,
+            range,
+            pred,
+            front,
+            kept: 0,
+            extracted: ArrayVec::new(),
+            leaf_id: 0,
+            marker: PhantomData,
+        }
+    }
+}
+
+#[test]
+fn retain() {
+    let mut t = Tree::<_, 4>::default();
+    for val in (0..1000).map(|v| v * 7919 % 1000) {
+        t.insert(val);
+    }
+
+    t.retain(|v| v % 3 == 0);
+    assert_eq!(t.validate(), Ok(()));
+    assert_eq!(
+        t.iter().copied().collect::<Vec<_>>(),
+        (0..1000).step_by(3).collect::<Vec<_>>()
+    );
+
+    t.retain(|_| false);
+    assert!(t.is_empty());
+    assert_eq!(t.validate(), Ok(()));
+}
+
+#[test]
+fn extract_if() {
+    for m in [2, 3, 5, 7] {
+        let mut t = Tree::<_, 5>::default();
+        for val in (0..1000).map(|v| v * 7919 % 1000) {
+            t.insert(val);
+        }
+
+        let extracted: Vec<_> = t.extract_if(200..800, |v| v % m != 0).collect();
+        let expected: Vec<_> = (200..800).filter(|v| v % m != 0).collect();
+        assert_eq!(extracted, expected);
+        assert_eq!(t.validate(), Ok(()));
+        assert_eq!(
+            t.iter().copied().collect::<Vec<_>>(),
+            (0..1000)
+                .filter(|v| !(200..800).contains(v) || v % m == 0)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    let mut t = Tree::<_, 3>::default();
+    for val in 0..100 {
+        t.insert(val);
+    }
+    assert_eq!(t.extract_if(.., |_| true).count(), 100);
+    assert!(t.is_empty());
+    assert_eq!(t.validate(), Ok(()));
+    assert_eq!(t.extract_if(.., |_| true).next(), None);
+}
+
+#[test]
+fn extract_if_dropped() {
+    let mut t = Tree::<_, 4>::default();
+    for val in 0..100 {
+        t.insert(val);
+    }
+
+    let mut iter = t.extract_if(10.., |v| v % 2 == 0);
+    assert_eq!(iter.next(), Some(10));
+    assert_eq!(iter.next(), Some(12));
+    drop(iter);
+    assert_eq!(t.validate(), Ok(()));
+    assert_eq!(t.len(), 98);
+    assert_eq!(t.get(&11), Some(&11));
+    assert_eq!(t.get(&12), None);
+    assert_eq!(t.get(&14), Some(&14));
+    assert_eq!(t.get(&98), Some(&98));
+}
+
+#[test]
+fn extract_if_dropped_after_one() {
+    let mut t = Tree::<_, 16>::default();
+    for val in 0..100 {
+        t.insert(val);
+    }
+
+    let mut iter = t.extract_if(.., |v| v % 2 == 0);
+    assert_eq!(iter.next(), Some(0));
+    drop(iter);
+    assert_eq!(t.validate(), Ok(()));
+    assert_eq!(t.len(), 99);
+    for val in (2..100).step_by(2) {
+        assert_eq!(t.get(&val), Some(&val));
+    }
+}
+
+#[test]
+fn extract_if_panicking_predicate() {
+    let mut t = Tree::<_, 16>::default();
+    for val in 0..100 {
+        t.insert(val);
+    }
+
+    // the leaf holding 50 is left as it was, the ones before it are done
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        t.extract_if(.., |&v| {
+            assert!(v != 50);
+            v % 2 == 0
+        })
+        .count()
+    }));
+    assert!(result.is_err());
+    assert_eq!(t.validate(), Ok(()));
+    assert_eq!(t.get(&0), None);
+    assert!((1..100).step_by(2).all(|v| t.get(&v).is_some()));
+    assert!((50..100).all(|v| t.get(&v).is_some()));
+}
+
+#[test]
+fn retain_panicking_predicate() {
+    let mut t = Tree::<_, 4>::default();
+    for val in 0..100 {
+        t.insert(val);
+    }
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        t.retain(|&v| {
+            assert!(v != 50);
+            v % 2 == 0
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(t.validate(), Ok(()));
+    assert!(t.is_empty());
+    t.insert(1);
+    assert_eq!(t.len(), 1);
+}
@@ -0,0 +1,166 @@
+use core::borrow::Borrow;
+use core::ops::Deref;
+
+pub use arrayvec::CapacityError;
+
+use super::{Compare, Fixed, Natural, Tree};
+
+/// B-Tree holding at most `NODES` nodes, which never allocates.
+///
+/// The arena and the free list are arrays of `NODES` entries kept inline in the tree,
+/// and an insertion which could need more nodes than the vacant ones is rejected
+/// before the tree is touched, so a failed insertion never leaves a node half-split.
+///
+/// It dereferences to the inner [`Tree`] for the read-only methods.
+#[derive(Debug)]
+pub struct StaticTree<T, const M: usize, const NODES: usize, C = Natural> {
+    tree: Tree<T, M, (), C, Fixed<NODES>>,
+}
+
+impl<T, const M: usize, const NODES: usize, C> Default for StaticTree<T, M, NODES, C>
+where
+    C: Compare<T> + Default,
+{
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T, const M: usize, const NODES: usize, C> StaticTree<T, M, NODES, C>
+where
+    C: Compare<T>,
+{
+    /// Creates an empty tree ordering its values by the comparator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `NODES` is zero.
+    pub fn with_comparator(cmp: C) -> Self {
+        assert!(NODES > 0, "a tree needs at least one node");
+        StaticTree {
+            tree: Tree::with_comparator(cmp),
+        }
+    }
+
+    /// Returns the maximum number of nodes of the tree.
+    pub fn node_capacity(&self) -> usize {
+        NODES
+    }
+
+    /// Returns the number of nodes which are not in use.
+    fn vacant_nodes(&self) -> usize {
+        self.tree.free.len() + NODES - self.tree.arena.len()
+    }
+
+    /// Adds a value to the tree.
+    /// Returns `Ok(false)` if an equal value is in the tree already.
+    ///
+    /// Gives the value back in the error if separating the full nodes
+    /// on the way up could take more nodes than the vacant ones.
+    pub fn try_insert(&mut self, value: T) -> Result<bool, CapacityError<T>> {
+        let tree = &mut self.tree;
+        let (leaf_id, idx) = match tree.search_by(&|v: &T| tree.cmp.compare(v, &value)) {
+            Ok(_) => return Ok(false),
+            Err(position) => position,
+        };
+        if tree.nodes_for_insert(leaf_id) > self.vacant_nodes() {
+            return Err(CapacityError::new(value));
+        }
+        let tree = &mut self.tree;
+        tree.insert_at(leaf_id, idx, value, None);
+        tree.len += 1;
+        Ok(true)
+    }
+
+    /// Removes the value equals to the given one from the tree and returns it.
+    pub fn delete<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        self.tree.delete(value)
+    }
+
+    /// Removes the smallest value from the tree and returns it.
+    pub fn pop_first(&mut self) -> Option<T> {
+        self.tree.pop_first()
+    }
+
+    /// Removes the largest value from the tree and returns it.
+    pub fn pop_last(&mut self) -> Option<T> {
+        self.tree.pop_last()
+    }
+}
+
+impl<T, const M: usize, const NODES: usize, C> Deref for StaticTree<T, M, NODES, C> {
+    type Target = Tree<T, M, (), C, Fixed<NODES>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_insert_full() {
+        let mut t = StaticTree::<u32, 3, 7>::default();
+        let mut inserted = Vec::new();
+        for val in (0..100).map(|v| v * 7919 % 100) {
+            match t.try_insert(val) {
+                Ok(true) => inserted.push(val),
+                Ok(false) => unreachable!(),
+                Err(err) => {
+                    assert_eq!(err.element(), val);
+                    assert_eq!(t.validate(), Ok(()));
+                }
+            }
+            assert!(t.arena.len() <= t.node_capacity());
+        }
+        assert!(inserted.len() >= 7);
+        assert_eq!(t.len(), inserted.len());
+        assert_eq!(t.try_insert(inserted[0]), Ok(false));
+
+        inserted.sort();
+        assert!(t.iter().copied().eq(inserted.iter().copied()));
+    }
+
+    #[test]
+    fn try_insert_needing_split_cascade() {
+        // the last value splits the full leaf and root and grows a new root, three nodes with two left
+        let mut t = StaticTree::<u32, 3, 6>::default();
+        for val in 0..6 {
+            assert_eq!(t.try_insert(val), Ok(true));
+        }
+        assert_eq!(t.try_insert(6).map_err(|err| err.element()), Err(6));
+        assert_eq!(t.validate(), Ok(()));
+        assert_eq!(t.len(), 6);
+        assert!(t.iter().copied().eq(0..6));
+    }
+
+    #[test]
+    fn reuse_after_delete() {
+        let mut t = StaticTree::<u32, 4, 16>::default();
+        let mut val = 0;
+        while t.try_insert(val).is_ok() {
+            val += 1;
+        }
+        let len = t.len();
+
+        for _ in 0..10 {
+            for v in 0..len as u32 / 2 {
+                assert_eq!(t.delete(&v), Some(v));
+            }
+            for v in 0..len as u32 / 2 {
+                assert_eq!(t.try_insert(v), Ok(true));
+            }
+            assert_eq!(t.validate(), Ok(()));
+        }
+        assert_eq!(t.pop_first(), Some(0));
+        assert_eq!(t.pop_last(), Some(len as u32 - 1));
+        assert_eq!(t.arena.len(), t.node_capacity());
+    }
+}
@@ -0,0 +1,551 @@
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+
+use arrayvec::ArrayVec;
+
+use super::{Compare, Heap, Natural, Node, Storage, Summary, Tree};
+
+/// An iterator over a sub-range of values in a `Tree`.
+///
+/// Created by [`Tree::range`].
+#[derive(Debug)]
+pub struct Range<'a, T, const M: usize, S = (), C = Natural, A: Storage = Heap> {
+    tree: &'a Tree<T, M, S, C, A>,
+    front: Option<(usize, usize)>,
+    back: Option<(usize, usize)>,
+}
+
+impl<'a, T, const M: usize, S, C, A> Range<'a, T, M, S, C, A>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+    A: Storage,
+{
+    pub(super) fn new(
+        tree: &'a Tree<T, M, S, C, A>,
+        front: Option<(usize, usize)>,
+        back: Option<(usize, usize)>,
+    ) -> Self {
+        Range { tree, front, back }
+    }
+}
+
+impl<'a, T, const M: usize, S, C, A: Storage> Clone for Range<'a, T, M, S, C, A> {
+    fn clone(&self) -> Self {
+        Range {
+            tree: self.tree,
+            front: self.front,
+            back: self.back,
+        }
+    }
+}
+
+impl<'a, T, const M: usize, S, C, A> Iterator for Range<'a, T, M, S, C, A>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+    A: Storage,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node_id, idx) = self.front?;
+        if self.front == self.back {
+            self.front = None;
+            self.back = None;
+        } else {
+            self.front = self.tree.next_handle((node_id, idx));
+        }
+        Some(&self.tree.arena[node_id].values[idx])
+    }
+}
+
+impl<'a, T, const M: usize, S, C, A> DoubleEndedIterator for Range<'a, T, M, S, C, A>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+    A: Storage,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (node_id, idx) = self.back?;
+        if self.front == self.back {
+            self.front = None;
+            self.back = None;
+        } else {
+            self.back = self.tree.prev_handle((node_id, idx));
+        }
+        Some(&self.tree.arena[node_id].values[idx])
+    }
+}
+
+impl<'a, T, const M: usize, S, C, A> FusedIterator for Range<'a, T, M, S, C, A>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+    A: Storage,
+{
+}
+
+/// An iterator over the values of a `Tree` in ascending order.
+///
+/// Created by [`Tree::iter`].
+#[derive(Debug)]
+pub struct Iter<'a, T, const M: usize, S = (), C = Natural, A: Storage = Heap> {
+    range: Range<'a, T, M, S, C, A>,
+    length: usize,
+}
+
+impl<'a, T, const M: usize, S, C, A> Iter<'a, T, M, S, C, A>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+    A: Storage,
+{
+    pub(super) fn new(range: Range<'a, T, M, S, C, A>, length: usize) -> Self {
+        Iter { range, length }
+    }
+}
+
+impl<'a, T, const M: usize, S, C, A: Storage> Clone for Iter<'a, T, M, S, C, A> {
+    fn clone(&self) -> Self {
+        Iter {
+            range: self.range.clone(),
+            length: self.length,
+        }
+    }
+}
+
+impl<'a, T, const M: usize, S, C, A> Iterator for Iter<'a, T, M, S, C, A>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+    A: Storage,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.length == 0 {
+            return None;
+        }
+        self.length -= 1;
+        self.range.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.length, Some(self.length))
+    }
+}
+
+impl<'a, T, const M: usize, S, C, A> DoubleEndedIterator for Iter<'a, T, M, S, C, A>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+    A: Storage,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.length == 0 {
+            return None;
+        }
+        self.length -= 1;
+        self.range.next_back()
+    }
+}
+
+impl<'a, T, const M: usize, S, C, A> ExactSizeIterator for Iter<'a, T, M, S, C, A>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+    A: Storage,
+{
+}
+
+impl<'a, T, const M: usize, S, C, A> FusedIterator for Iter<'a, T, M, S, C, A>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+    A: Storage,
+{
+}
+
+impl<'a, T, const M: usize, S, C, A> IntoIterator for &'a Tree<T, M, S, C, A>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+    A: Storage,
+{
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, M, S, C, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An owning iterator over the values of a `Tree` in ascending order.
+///
+/// Created by the `into_iter` method on `Tree`. The values are moved out
+/// of the nodes while the iterator is consumed.
+#[derive(Debug)]
+pub struct IntoIter<T, const M: usize, S = ()> {
+    arena: Vec<Node<T, M, S>>,
+    /// The path from the root to the leaf the front is in, with the index
+    /// of the child being visited in every node and whether it's reversed.
+    front: Vec<(usize, usize, bool)>,
+    /// The same path for the back.
+    back: Vec<(usize, usize, bool)>,
+    length: usize,
+}
+
+impl<T, const M: usize, S> IntoIter<T, M, S> {
+    /// Takes the `length` values of the tree rooted at `root_id` out of the arena.
+    pub(super) fn new(arena: Vec<Node<T, M, S>>, root_id: usize, length: usize) -> Self {
+        let mut it = IntoIter {
+            arena,
+            front: Vec::new(),
+            back: Vec::new(),
+            length,
+        };
+        it.push_front_path(root_id);
+        it.push_back_path(root_id);
+        it
+    }
+
+    /// Goes down from the node to the leftmost leaf of its subtree,
+    /// reversing the nodes the back hasn't reached.
+    fn push_front_path(&mut self, mut node_id: usize) {
+        loop {
+            let reversed = !self.back.iter().any(|&(id, ..)| id == node_id);
+            if reversed {
+                self.arena[node_id].values.reverse();
+            }
+            self.front.push((node_id, 0, reversed));
+            match self.arena[node_id].children.first() {
+                Some(&child_id) => node_id = child_id,
+                None => return,
+            }
+        }
+    }
+
+    /// Goes down from the node to the rightmost leaf of its subtree.
+    fn push_back_path(&mut self, mut node_id: usize) {
+        loop {
+            let reversed = self
+                .front
+                .iter()
+                .any(|&(id, _, reversed)| id == node_id && reversed);
+            let children = &self.arena[node_id].children;
+            let Some(&child_id) = children.last() else {
+                self.back.push((node_id, 0, reversed));
+                return;
+            };
+            self.back.push((node_id, children.len() - 1, reversed));
+            node_id = child_id;
+        }
+    }
+
+    /// Takes the smallest value left in the node.
+    fn take_first(values: &mut ArrayVec<T, M>, reversed: bool) -> T {
+        if reversed {
+            values.pop().unwrap()
+        } else {
+            values.remove(0)
+        }
+    }
+
+    /// Takes the largest value left in the node.
+    fn take_last(values: &mut ArrayVec<T, M>, reversed: bool) -> T {
+        if reversed {
+            values.remove(0)
+        } else {
+            values.pop().unwrap()
+        }
+    }
+}
+
+// The front takes values from the beginning of the nodes and the back from their end,
+// so the values left in a node are always the ones neither end has reached yet.
+// The nodes the front reaches first are reversed, so both ends mostly pop
+// from the end of a node, only the ones on both paths are shifted.
+// Children are never removed, an exhausted leaf is just left empty.
+impl<T, const M: usize, S> Iterator for IntoIter<T, M, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.length == 0 {
+            return None;
+        }
+        self.length -= 1;
+        loop {
+            let (node_id, child_idx, reversed) = *self.front.last().unwrap();
+            let node = &mut self.arena[node_id];
+            if node.children.is_empty() && !node.values.is_empty() {
+                return Some(Self::take_first(&mut node.values, reversed));
+            }
+            if node.children.is_empty() || child_idx + 1 == node.children.len() {
+                // the subtree is exhausted
+                self.front.pop();
+                continue;
+            }
+            // the separator after the visited child comes before the next child
+            let value = Self::take_first(&mut node.values, reversed);
+            let next_child_id = node.children[child_idx + 1];
+            self.front.last_mut().unwrap().1 += 1;
+            self.push_front_path(next_child_id);
+            return Some(value);
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.length, Some(self.length))
+    }
+}
+
+impl<T, const M: usize, S> DoubleEndedIterator for IntoIter<T, M, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.length == 0 {
+            return None;
+        }
+        self.length -= 1;
+        loop {
+            let (node_id, child_idx, reversed) = *self.back.last().unwrap();
+            let node = &mut self.arena[node_id];
+            if node.children.is_empty() && !node.values.is_empty() {
+                return Some(Self::take_last(&mut node.values, reversed));
+            }
+            if node.children.is_empty() || child_idx == 0 {
+                // the subtree is exhausted
+                self.back.pop();
+                continue;
+            }
+            // the separator before the visited child comes after the previous child
+            let value = Self::take_last(&mut node.values, reversed);
+            let prev_child_id = node.children[child_idx - 1];
+            self.back.last_mut().unwrap().1 -= 1;
+            self.push_back_path(prev_child_id);
+            return Some(value);
+        }
+    }
+}
+
+impl<T, const M: usize, S> ExactSizeIterator for IntoIter<T, M, S> {}
+
+impl<T, const M: usize, S> FusedIterator for IntoIter<T, M, S> {}
+
+impl<T, const M: usize, S, C> IntoIterator for Tree<T, M, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    type Item = T;
+    type IntoIter = IntoIter<T, M, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self.arena, self.root_id, self.len)
+    }
+}
+
+#[test]
+fn range_bounds() {
+    use core::ops::Bound;
+
+    let mut t = Tree::<_, 3>::default();
+    for val in 1..10 {
+        t.insert(val);
+    }
+
+    assert_eq!(t.range(3..6).copied().collect::<Vec<_>>(), vec![3, 4, 5]);
+    assert_eq!(
+        t.range(3..=6).copied().collect::<Vec<_>>(),
+        vec![3, 4, 5, 6]
+    );
+    assert_eq!(t.range(..3).copied().collect::<Vec<_>>(), vec![1, 2]);
+    assert_eq!(t.range(7..).copied().collect::<Vec<_>>(), vec![7, 8, 9]);
+    assert_eq!(t.range(..).count(), 9);
+    assert_eq!(
+        t.range((Bound::Excluded(4), Bound::Excluded(8)))
+            .copied()
+            .collect::<Vec<_>>(),
+        vec![5, 6, 7]
+    );
+    assert_eq!(t.range(-5..0).count(), 0);
+    assert_eq!(t.range(10..).count(), 0);
+    assert_eq!(t.range(5..5).count(), 0);
+    assert_eq!(t.range((Bound::Excluded(5), Bound::Excluded(6))).count(), 0);
+}
+
+#[test]
+fn range_missing_bounds() {
+    let mut t = Tree::<_, 4>::default();
+    for val in (0..100).map(|v| v * 2) {
+        t.insert(val);
+    }
+
+    assert_eq!(
+        t.range(11..=19).copied().collect::<Vec<_>>(),
+        vec![12, 14, 16, 18]
+    );
+    assert_eq!(t.range(-1..3).copied().collect::<Vec<_>>(), vec![0, 2]);
+    assert_eq!(
+        t.range(195..1000).copied().collect::<Vec<_>>(),
+        vec![196, 198]
+    );
+}
+
+#[test]
+fn range_double_ended() {
+    let mut t = Tree::<_, 3>::default();
+    for val in 1..20 {
+        t.insert(val);
+    }
+
+    let mut r = t.range(5..10);
+    assert_eq!(r.next_back(), Some(&9));
+    assert_eq!(r.next(), Some(&5));
+    assert_eq!(r.next_back(), Some(&8));
+    assert_eq!(r.next(), Some(&6));
+    assert_eq!(r.next(), Some(&7));
+    assert_eq!(r.next_back(), None);
+    assert_eq!(r.next(), None);
+
+    assert_eq!(
+        t.range(..=5).rev().copied().collect::<Vec<_>>(),
+        vec![5, 4, 3, 2, 1]
+    );
+}
+
+#[test]
+fn range_empty_tree() {
+    let t = Tree::<usize, 3>::default();
+    assert_eq!(t.range(..).next(), None);
+    assert_eq!(t.range(1..2).next_back(), None);
+}
+
+#[test]
+fn range_shuffled() {
+    range_shuffled_order::<3>();
+    range_shuffled_order::<4>();
+    range_shuffled_order::<5>();
+    range_shuffled_order::<8>();
+}
+
+#[cfg(test)]
+fn range_shuffled_order<const M: usize>() {
+    let vals: Vec<usize> = (0..1000).map(|v| v * 7919 % 1000).collect();
+    for step in [3, 4, 5, 8] {
+        let mut t = Tree::<_, M>::default();
+        for &val in vals.iter() {
+            t.insert(val);
+        }
+        for &val in vals.iter().filter(|&v| v % step == 0) {
+            t.delete(&val);
+        }
+
+        let expected: Vec<_> = (250..750).filter(|v| v % step != 0).collect();
+        assert_eq!(t.range(250..750).copied().collect::<Vec<_>>(), expected);
+
+        let mut expected = expected;
+        expected.reverse();
+        assert_eq!(
+            t.range(250..750).rev().copied().collect::<Vec<_>>(),
+            expected
+        );
+    }
+}
+
+#[test]
+fn iter_in_order() {
+    let vals: Vec<usize> = (0..500).map(|v| v * 7919 % 500).collect();
+    let mut t = Tree::<_, 5>::default();
+    for &val in vals.iter() {
+        t.insert(val);
+    }
+    for &val in vals.iter().filter(|&v| v % 7 == 0) {
+        t.delete(&val);
+    }
+
+    let expected: Vec<_> = (0..500).filter(|v| v % 7 != 0).collect();
+    assert_eq!(t.iter().copied().collect::<Vec<_>>(), expected);
+    assert_eq!(t.iter().len(), expected.len());
+
+    let mut expected = expected;
+    expected.reverse();
+    assert_eq!(t.iter().rev().copied().collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn iter_exact_size() {
+    let mut t = Tree::<_, 3>::default();
+    for val in 1..8 {
+        t.insert(val);
+    }
+    t.insert(3);
+
+    let mut it = t.iter();
+    assert_eq!(it.len(), 7);
+    assert_eq!(it.next(), Some(&1));
+    assert_eq!(it.next_back(), Some(&7));
+    assert_eq!(it.size_hint(), (5, Some(5)));
+    assert_eq!(it.by_ref().count(), 5);
+    assert_eq!(it.len(), 0);
+    assert_eq!(it.next(), None);
+    assert_eq!(it.next_back(), None);
+
+    let t = Tree::<usize, 3>::default();
+    assert_eq!(t.iter().len(), 0);
+    assert_eq!(t.iter().next(), None);
+}
+
+#[test]
+fn into_iter() {
+    let mut t = Tree::<_, 3>::default();
+    for val in (1..10).rev() {
+        t.insert(val);
+    }
+
+    let mut sum = 0;
+    for &val in &t {
+        sum += val;
+    }
+    assert_eq!(sum, 45);
+
+    let mut it = t.into_iter();
+    assert_eq!(it.len(), 9);
+    assert_eq!(it.next_back(), Some(9));
+    assert_eq!(it.collect::<Vec<_>>(), vec![1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn into_iter_both_ends() {
+    for n in [0, 1, 2, 10, 100, 1000] {
+        let mut t = Tree::<_, 4>::default();
+        for val in (0..n).map(|v| v * 7919 % n) {
+            t.insert(val.to_string());
+        }
+
+        // takes two values from the front for every one from the back
+        let mut it = t.into_iter();
+        let (mut front, mut back) = (Vec::new(), Vec::new());
+        for step in 0.. {
+            let val = if step % 3 == 2 {
+                it.next_back()
+            } else {
+                it.next()
+            };
+            let Some(val) = val else { break };
+            if step % 3 == 2 {
+                back.push(val);
+            } else {
+                front.push(val);
+            }
+            assert_eq!(it.len(), n - front.len() - back.len());
+        }
+        assert_eq!(it.next(), None);
+        assert_eq!(it.next_back(), None);
+
+        back.reverse();
+        front.extend(back);
+        let mut expected: Vec<_> = (0..n).map(|v| v.to_string()).collect();
+        expected.sort();
+        assert_eq!(front, expected);
+    }
+}
@@ -0,0 +1,197 @@
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+
+use super::Tree;
+
+mod entry;
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
+
+/// A key-value pair which is ordered by its key only.
+#[derive(Debug)]
+struct KeyValue<K, V> {
+    key: K,
+    value: V,
+}
+
+impl<K: PartialEq, V> PartialEq for KeyValue<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<K: Eq, V> Eq for KeyValue<K, V> {}
+
+impl<K: PartialOrd, V> PartialOrd for KeyValue<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.key.partial_cmp(&other.key)
+    }
+}
+
+impl<K: Ord, V> Ord for KeyValue<K, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+/// An ordered map based on the arena B-Tree.
+///
+/// It shares the insertion and rebalancing logic with `Tree`
+/// by storing the key-value pairs in the nodes directly.
+#[derive(Debug)]
+pub struct TreeMap<K, V, const M: usize> {
+    tree: Tree<KeyValue<K, V>, M>,
+}
+
+impl<K, V, const M: usize> Default for TreeMap<K, V, M>
+where
+    K: Ord,
+{
+    fn default() -> Self {
+        TreeMap {
+            tree: Tree::default(),
+        }
+    }
+}
+
+impl<K, V, const M: usize> TreeMap<K, V, M>
+where
+    K: Ord,
+{
+    fn compare_key<Q>(key: &Q) -> impl Fn(&KeyValue<K, V>) -> Ordering + '_
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        move |kv| kv.key.borrow().cmp(key)
+    }
+
+    /// Inserts a key-value pair into the map.
+    /// Returns the old value if the key was already present.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => Some(entry.insert(value)),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                None
+            }
+        }
+    }
+
+    /// Gets the entry of the key for in-place manipulation, with a single descent.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, M> {
+        let found = self.tree.search_by(&Self::compare_key(&key));
+        match found {
+            Ok(handle) => Entry::Occupied(OccupiedEntry {
+                tree: &mut self.tree,
+                handle,
+            }),
+            Err(position) => Entry::Vacant(VacantEntry {
+                tree: &mut self.tree,
+                key,
+                position,
+            }),
+        }
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (node_id, idx) = self.tree.get_handle_by(&Self::compare_key(key))?;
+        Some(&self.tree.arena[node_id].values[idx].value)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (node_id, idx) = self.tree.get_handle_by(&Self::compare_key(key))?;
+        Some(&mut self.tree.arena[node_id].values[idx].value)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.tree.get_handle_by(&Self::compare_key(key)).is_some()
+    }
+
+    /// Removes a key from the map, returning the stored key and value.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.tree
+            .remove_by(&Self::compare_key(key))
+            .map(|KeyValue { key, value }| (key, value))
+    }
+}
+
+#[test]
+fn map_insert_get() {
+    let mut m = TreeMap::<_, _, 3>::default();
+    for key in 0..20 {
+        assert_eq!(m.insert(key, key * 10), None);
+    }
+    assert_eq!(m.insert(5, 55), Some(50));
+    assert_eq!(m.len(), 20);
+
+    assert_eq!(m.get(&5), Some(&55));
+    assert_eq!(m.get(&19), Some(&190));
+    assert_eq!(m.get(&20), None);
+    assert!(m.contains_key(&0));
+    assert!(!m.contains_key(&-1));
+
+    *m.get_mut(&7).unwrap() += 1;
+    assert_eq!(m.get(&7), Some(&71));
+}
+
+#[test]
+fn map_remove() {
+    let keys: Vec<usize> = (0..300).map(|v| v * 7919 % 300).collect();
+    let mut m = TreeMap::<_, _, 5>::default();
+    for &key in keys.iter() {
+        m.insert(key, key + 1);
+    }
+    for &key in keys.iter().filter(|&k| k % 2 == 0) {
+        assert_eq!(m.remove(&key), Some((key, key + 1)));
+    }
+    assert_eq!(m.remove(&0), None);
+    assert_eq!(m.len(), 150);
+
+    for key in 0..300 {
+        if key % 2 == 0 {
+            assert_eq!(m.get(&key), None);
+        } else {
+            assert_eq!(m.get(&key), Some(&(key + 1)));
+        }
+    }
+}
+
+#[test]
+fn map_non_copy() {
+    let mut m = TreeMap::<String, Vec<u8>, 4>::default();
+    for (idx, key) in ["b", "a", "d", "c", "e"].iter().enumerate() {
+        m.insert(key.to_string(), vec![idx as u8]);
+    }
+
+    assert_eq!(m.get("d"), Some(&vec![2]));
+    m.get_mut("d").unwrap().push(9);
+    assert_eq!(m.get("d"), Some(&vec![2, 9]));
+    assert_eq!(m.insert("a".to_string(), vec![]), Some(vec![1]));
+    assert_eq!(m.remove("c"), Some(("c".to_string(), vec![3])));
+    assert!(!m.contains_key("c"));
+}
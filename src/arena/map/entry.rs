@@ -0,0 +1,218 @@
+use super::{KeyValue, Tree};
+
+/// A view into a single entry of a `TreeMap`, which is either vacant or occupied.
+///
+/// Created by [`TreeMap::entry`](super::TreeMap::entry).
+#[derive(Debug)]
+pub enum Entry<'a, K, V, const M: usize> {
+    Vacant(VacantEntry<'a, K, V, M>),
+    Occupied(OccupiedEntry<'a, K, V, M>),
+}
+
+/// A view into a vacant entry of a `TreeMap`.
+///
+/// It remembers the leaf and the index found by the descent,
+/// so inserting starts from there and only walks up if the leaf separates.
+#[derive(Debug)]
+pub struct VacantEntry<'a, K, V, const M: usize> {
+    pub(super) tree: &'a mut Tree<KeyValue<K, V>, M>,
+    pub(super) key: K,
+    pub(super) position: (usize, usize),
+}
+
+/// A view into an occupied entry of a `TreeMap`.
+#[derive(Debug)]
+pub struct OccupiedEntry<'a, K, V, const M: usize> {
+    pub(super) tree: &'a mut Tree<KeyValue<K, V>, M>,
+    pub(super) handle: (usize, usize),
+}
+
+impl<'a, K, V, const M: usize> Entry<'a, K, V, M>
+where
+    K: Ord,
+{
+    /// Inserts the default value if the entry is vacant,
+    /// and returns a mutable reference to the value.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Inserts the result of the function if the entry is vacant,
+    /// and returns a mutable reference to the value.
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Calls the function with the value if the entry is occupied.
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+}
+
+impl<'a, K, V, const M: usize> Entry<'a, K, V, M>
+where
+    K: Ord,
+    V: Default,
+{
+    /// Inserts the default value of the type if the entry is vacant,
+    /// and returns a mutable reference to the value.
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, K, V, const M: usize> VacantEntry<'a, K, V, M>
+where
+    K: Ord,
+{
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Takes the key back without inserting.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Inserts the value at the position found by the descent,
+    /// and returns a mutable reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        let (leaf_id, idx) = self.position;
+        let key_value = KeyValue {
+            key: self.key,
+            value,
+        };
+        let (node_id, idx) = self.tree.insert_at(leaf_id, idx, key_value, None);
+        self.tree.len += 1;
+        &mut self.tree.arena[node_id].values[idx].value
+    }
+}
+
+impl<'a, K, V, const M: usize> OccupiedEntry<'a, K, V, M>
+where
+    K: Ord,
+{
+    fn key_value(&self) -> &KeyValue<K, V> {
+        let (node_id, idx) = self.handle;
+        &self.tree.arena[node_id].values[idx]
+    }
+
+    pub fn key(&self) -> &K {
+        &self.key_value().key
+    }
+
+    pub fn get(&self) -> &V {
+        &self.key_value().value
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        let (node_id, idx) = self.handle;
+        &mut self.tree.arena[node_id].values[idx].value
+    }
+
+    /// Converts the entry into a mutable reference to its value,
+    /// which lives as long as the borrow of the map.
+    pub fn into_mut(self) -> &'a mut V {
+        let (node_id, idx) = self.handle;
+        &mut self.tree.arena[node_id].values[idx].value
+    }
+
+    /// Replaces the value of the entry, and returns the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        core::mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes the entry from the map, and returns its value.
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    /// Removes the entry from the map, and returns its key and value.
+    pub fn remove_entry(self) -> (K, V) {
+        let KeyValue { key, value } = self.tree.remove_handle(self.handle);
+        (key, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::TreeMap;
+    use super::*;
+
+    #[test]
+    fn entry_counting() {
+        let mut m = TreeMap::<_, _, 3>::default();
+        let words = "the quick brown fox jumps over the lazy dog the end";
+        for word in words.split(' ') {
+            *m.entry(word).or_insert(0) += 1;
+        }
+        assert_eq!(m.len(), 9);
+        assert_eq!(m.get("the"), Some(&3));
+        assert_eq!(m.get("fox"), Some(&1));
+
+        m.entry("fox").and_modify(|v| *v += 10).or_default();
+        m.entry("cat").and_modify(|v| *v += 10).or_default();
+        assert_eq!(m.get("fox"), Some(&11));
+        assert_eq!(m.get("cat"), Some(&0));
+    }
+
+    #[test]
+    fn entry_many() {
+        let keys: Vec<usize> = (0..1000).map(|v| v * 7919 % 1000).collect();
+        let mut m = TreeMap::<_, _, 4>::default();
+        for &key in keys.iter() {
+            match m.entry(key) {
+                Entry::Vacant(entry) => {
+                    assert_eq!(entry.key(), &key);
+                    assert_eq!(*entry.insert(key * 2), key * 2);
+                }
+                Entry::Occupied(_) => unreachable!(),
+            }
+        }
+        assert_eq!(m.len(), 1000);
+        assert_eq!(m.tree.validate(), Ok(()));
+
+        for &key in keys.iter() {
+            match m.entry(key) {
+                Entry::Occupied(mut entry) => {
+                    assert_eq!(entry.get(), &(key * 2));
+                    if key % 2 == 0 {
+                        assert_eq!(entry.remove_entry(), (key, key * 2));
+                    } else {
+                        assert_eq!(entry.insert(key), key * 2);
+                    }
+                }
+                Entry::Vacant(_) => unreachable!(),
+            }
+        }
+        assert_eq!(m.len(), 500);
+        assert_eq!(m.tree.validate(), Ok(()));
+        for key in 0..1000 {
+            let expected = if key % 2 == 0 { None } else { Some(&key) };
+            assert_eq!(m.get(&key), expected);
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+
+use super::{Size, Tree};
+
+/// A value with the sequence number of its insertion,
+/// which orders equal values by insertion.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Sequenced<T> {
+    value: T,
+    seq: u64,
+}
+
+/// An ordered multiset based on the arena B-Tree, which keeps equal values
+/// in insertion order.
+///
+/// Every value is stored with a sequence number, so the values in the nodes stay
+/// distinct and the tree shares its insertion and rebalancing logic with `Tree`.
+/// The nodes keep their subtree sizes to count the equal values.
+#[derive(Debug)]
+pub struct MultiSet<T, const M: usize> {
+    tree: Tree<Sequenced<T>, M, Size>,
+    next_seq: u64,
+}
+
+impl<T, const M: usize> Default for MultiSet<T, M>
+where
+    T: Ord,
+{
+    fn default() -> Self {
+        MultiSet {
+            tree: Tree::default(),
+            next_seq: 0,
+        }
+    }
+}
+
+impl<T, const M: usize> MultiSet<T, M>
+where
+    T: Ord,
+{
+    /// Compares with the value as if it were greater than all the equal values,
+    /// or less than them if `after` is not set. It never returns `Equal`,
+    /// which makes the search find the boundary of the run of equal values.
+    fn compare_bound<Q>(value: &Q, after: bool) -> impl Fn(&Sequenced<T>) -> Ordering + '_
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        move |v| match v.value.borrow().cmp(value) {
+            Ordering::Equal if after => Ordering::Less,
+            Ordering::Equal => Ordering::Greater,
+            ordering => ordering,
+        }
+    }
+
+    /// Adds a value to the multiset, after the values equal to it.
+    pub fn insert(&mut self, value: T) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.tree.insert(Sequenced { value, seq });
+    }
+
+    /// Returns the number of values in the multiset, counting duplicates.
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Returns the number of values equal to the given one.
+    pub fn count<Q>(&self, value: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.tree.count_less_by(&Self::compare_bound(value, true))
+            - self.tree.count_less_by(&Self::compare_bound(value, false))
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.first_equal(value).is_some()
+    }
+
+    /// Finds the position of the earliest inserted value equal to the given one.
+    fn first_equal<Q>(&self, value: &Q) -> Option<(usize, usize)>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let handle = self
+            .tree
+            .lower_handle_by(&Self::compare_bound(value, false))?;
+        if self.tree.value(handle).value.borrow() == value {
+            Some(handle)
+        } else {
+            None
+        }
+    }
+
+    /// Removes the earliest inserted value equal to the given one and returns it.
+    pub fn remove_one<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let handle = self.first_equal(value)?;
+        Some(self.tree.remove_handle(handle).value)
+    }
+
+    /// Removes all the values equal to the given one.
+    /// Returns the number of values removed.
+    pub fn remove_all<Q>(&mut self, value: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut count = 0;
+        while let Some(handle) = self.first_equal(value) {
+            self.tree.remove_handle(handle);
+            count += 1;
+        }
+        count
+    }
+
+    /// Gets an iterator that visits the values in ascending order,
+    /// and equal values in insertion order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.tree.iter().map(|v| &v.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An event ordered by its timestamp only.
+    #[derive(Debug)]
+    struct Event {
+        ts: u32,
+        id: u32,
+    }
+
+    impl PartialEq for Event {
+        fn eq(&self, other: &Self) -> bool {
+            self.ts == other.ts
+        }
+    }
+
+    impl Eq for Event {}
+
+    impl PartialOrd for Event {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Event {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.ts.cmp(&other.ts)
+        }
+    }
+
+    impl Borrow<u32> for Event {
+        fn borrow(&self) -> &u32 {
+            &self.ts
+        }
+    }
+
+    #[test]
+    fn multiset_count() {
+        let mut s = MultiSet::<_, 3>::default();
+        for val in (0..300).map(|v| v * 7919 % 300) {
+            s.insert(val % 30);
+        }
+        assert_eq!(s.len(), 300);
+        for val in 0..30 {
+            assert_eq!(s.count(&val), 10);
+            assert!(s.contains(&val));
+        }
+        assert_eq!(s.count(&30), 0);
+        assert!(!s.contains(&-1));
+
+        let vals: Vec<_> = s.iter().copied().collect();
+        let mut expected: Vec<_> = (0..300).map(|v| v % 30).collect();
+        expected.sort();
+        assert_eq!(vals, expected);
+    }
+
+    #[test]
+    fn multiset_insertion_order() {
+        let mut s = MultiSet::<_, 3>::default();
+        for id in 0..100 {
+            s.insert(Event { ts: id % 3, id });
+        }
+        let ids: Vec<_> = s.iter().filter(|e| e.ts == 1).map(|e| e.id).collect();
+        assert_eq!(ids, (0..100).filter(|id| id % 3 == 1).collect::<Vec<_>>());
+
+        for id in (0..100).filter(|id| id % 3 == 2) {
+            assert_eq!(s.remove_one(&2).map(|e| e.id), Some(id));
+        }
+        assert_eq!(s.remove_one(&2), None);
+        assert_eq!(s.tree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn multiset_remove_all() {
+        let mut s = MultiSet::<_, 4>::default();
+        for val in (0..1000).map(|v| v * 7919 % 1000) {
+            s.insert(val % 7);
+        }
+
+        assert_eq!(s.remove_all(&3), 143);
+        assert_eq!(s.count(&3), 0);
+        assert_eq!(s.remove_all(&3), 0);
+        assert_eq!(s.len(), 1000 - 143);
+        assert_eq!(s.tree.validate(), Ok(()));
+
+        for val in [0, 6, 1] {
+            let count = s.count(&val);
+            assert_eq!(s.remove_one(&val), Some(val));
+            assert_eq!(s.remove_all(&val), count - 1);
+            assert_eq!(s.tree.validate(), Ok(()));
+        }
+        let mut expected: Vec<_> = (0..1000)
+            .map(|v| v % 7)
+            .filter(|v| [2, 4, 5].contains(v))
+            .collect();
+        expected.sort();
+        assert_eq!(s.iter().copied().collect::<Vec<_>>(), expected);
+    }
+}
@@ -0,0 +1,187 @@
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::ops::{Bound, RangeBounds};
+
+#[cfg(test)]
+use super::Size;
+use super::{Compare, SubtreeSize, Summary, Tree};
+
+/// The order statistics, which need the subtree sizes from a summary like [`super::Size`].
+impl<T, const M: usize, S, C> Tree<T, M, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T> + SubtreeSize,
+{
+    /// Counts the values less than the given one,
+    /// or not greater than it if `inclusive` is set.
+    fn count_before<Q>(&self, value: &Q, inclusive: bool) -> usize
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        let mut count = 0;
+        let mut cur = &self.arena[self.root_id];
+        loop {
+            let (idx, found) = Self::binary_search(&self.cmp, &cur.values, value);
+            // the left child of the found value lies before it as well
+            let children = if found { idx + 1 } else { idx };
+            count += idx;
+            count += cur
+                .children
+                .iter()
+                .take(children)
+                .map(|&child_id| self.arena[child_id].summary.size())
+                .sum::<usize>();
+            if found {
+                return if inclusive { count + 1 } else { count };
+            }
+            if cur.is_leaf() {
+                return count;
+            }
+            cur = &self.arena[cur.children[idx]];
+        }
+    }
+
+    /// Counts the values for which the comparator function returns `Less`.
+    /// The function must never return `Equal`, so that equal values
+    /// spread over several nodes are all counted on the same side.
+    pub(super) fn count_less_by<F>(&self, f: &F) -> usize
+    where
+        F: Fn(&T) -> Ordering,
+    {
+        let mut count = 0;
+        let mut cur = &self.arena[self.root_id];
+        loop {
+            let (idx, _) = Self::binary_search_by(&cur.values, f);
+            count += idx;
+            count += cur
+                .children
+                .iter()
+                .take(idx)
+                .map(|&child_id| self.arena[child_id].summary.size())
+                .sum::<usize>();
+            if cur.is_leaf() {
+                return count;
+            }
+            cur = &self.arena[cur.children[idx]];
+        }
+    }
+
+    /// Returns the number of values in the tree less than the given one.
+    pub fn rank<Q>(&self, value: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        self.count_before(value, false)
+    }
+
+    /// Returns the `idx`-th smallest value in the tree, counting from zero.
+    pub fn select(&self, idx: usize) -> Option<&T> {
+        self.select_handle(idx).map(|handle| self.value(handle))
+    }
+
+    /// Finds the position of the `idx`-th smallest value.
+    fn select_handle(&self, idx: usize) -> Option<(usize, usize)> {
+        if idx >= self.len {
+            return None;
+        }
+        let mut idx = idx;
+        let mut cur = &self.arena[self.root_id];
+        'descend: loop {
+            if cur.is_leaf() {
+                return Some((cur.idx, idx));
+            }
+            for (value_idx, &child_id) in cur.children.iter().enumerate() {
+                let child = &self.arena[child_id];
+                match idx.checked_sub(child.summary.size()) {
+                    None => {
+                        cur = child;
+                        continue 'descend;
+                    }
+                    Some(0) => return Some((cur.idx, value_idx)),
+                    Some(rest) => idx = rest - 1,
+                }
+            }
+            unreachable!()
+        }
+    }
+
+    /// Returns the number of values in the tree lying in the range.
+    pub fn count_range<K, R>(&self, range: R) -> usize
+    where
+        T: Borrow<K>,
+        C: Compare<K>,
+        K: ?Sized,
+        R: RangeBounds<K>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(value) => self.count_before(value, false),
+            Bound::Excluded(value) => self.count_before(value, true),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(value) => self.count_before(value, true),
+            Bound::Excluded(value) => self.count_before(value, false),
+            Bound::Unbounded => self.len,
+        };
+        end.saturating_sub(start)
+    }
+}
+
+#[test]
+fn rank_select() {
+    let mut t = Tree::<_, 4, Size>::default();
+    for val in (0..500).map(|v| v * 7919 % 500) {
+        t.insert(val * 2);
+    }
+
+    for idx in 0..500 {
+        assert_eq!(t.select(idx), Some(&(idx * 2)));
+        assert_eq!(t.rank(&(idx * 2)), idx);
+        assert_eq!(t.rank(&(idx * 2 + 1)), idx + 1);
+    }
+    assert_eq!(t.select(500), None);
+    assert_eq!(t.rank(&2000), 500);
+
+    for val in (0..500).filter(|v| v % 3 == 0) {
+        t.delete(&(val * 2));
+    }
+    let vals: Vec<_> = t.iter().copied().collect();
+    for (idx, val) in vals.iter().enumerate() {
+        assert_eq!(t.select(idx), Some(val));
+        assert_eq!(t.rank(val), idx);
+    }
+    assert_eq!(t.select(vals.len()), None);
+}
+
+#[test]
+fn count_range() {
+    let mut t = Tree::<_, 3, Size>::default();
+    for val in 1..100 {
+        t.insert(val);
+    }
+    for val in (1..100).filter(|v| v % 10 == 0) {
+        t.delete(&val);
+    }
+
+    assert_eq!(t.count_range(..), 90);
+    assert_eq!(t.count_range(1..10), 9);
+    assert_eq!(t.count_range(1..=10), 9);
+    assert_eq!(t.count_range(5..=15), 10);
+    assert_eq!(t.count_range((Bound::Excluded(5), Bound::Excluded(15))), 8);
+    assert_eq!(t.count_range(50..), 45);
+    assert_eq!(t.count_range(..0), 0);
+    assert_eq!(t.count_range((Bound::Included(30), Bound::Excluded(20))), 0);
+    assert_eq!(t.count_range(20..30), t.range(20..30).count());
+}
+
+#[test]
+fn empty_tree() {
+    let t = Tree::<i32, 3, Size>::default();
+    assert_eq!(t.rank(&1), 0);
+    assert_eq!(t.select(0), None);
+    assert_eq!(t.count_range(..), 0);
+}
@@ -0,0 +1,112 @@
+use core::fmt;
+use core::marker::PhantomData;
+
+use serde::de::{Deserialize, Deserializer, Error, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+use super::{Compare, Summary, Tree};
+
+/// Serializes the values as a sequence in ascending order.
+impl<T, const M: usize, S, C> Serialize for Tree<T, M, S, C>
+where
+    T: Serialize,
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for value in self.iter() {
+            seq.serialize_element(value)?;
+        }
+        seq.end()
+    }
+}
+
+/// Deserializes a sequence of values in strictly ascending order,
+/// building the tree bottom-up like `Tree::from_sorted_iter`.
+/// Unsorted or duplicated values are an error.
+impl<'de, T, const M: usize, S, C> Deserialize<'de> for Tree<T, M, S, C>
+where
+    T: Deserialize<'de>,
+    C: Compare<T> + Default,
+    S: Summary<T>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(TreeVisitor(PhantomData))
+    }
+}
+
+struct TreeVisitor<T, const M: usize, S, C>(PhantomData<Tree<T, M, S, C>>);
+
+impl<'de, T, const M: usize, S, C> Visitor<'de> for TreeVisitor<T, M, S, C>
+where
+    T: Deserialize<'de>,
+    C: Compare<T> + Default,
+    S: Summary<T>,
+{
+    type Value = Tree<T, M, S, C>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a sequence of values in strictly ascending order")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut t = Tree::default();
+        // the first error of the sequence ends the values given to the tree
+        let mut error = None;
+        let values = core::iter::from_fn(|| {
+            seq.next_element().unwrap_or_else(|e| {
+                error = Some(e);
+                None
+            })
+        });
+        let loaded = t.try_load_sorted(values, M - 1);
+        if let Some(e) = error {
+            return Err(e);
+        }
+        match loaded {
+            Ok(()) => Ok(t),
+            Err(idx) => Err(A::Error::custom(format_args!(
+                "value at index {} is not greater than the previous one, \
+                 values must be sorted and unique",
+                idx
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn serde_round_trip() {
+        let t = Tree::<_, 4>::from_sorted_iter(0..1000);
+        let json = serde_json::to_string(&t).unwrap();
+        let expected: Vec<_> = (0..1000).collect();
+        assert_eq!(json, serde_json::to_string(&expected).unwrap());
+
+        let t: Tree<i32, 4> = serde_json::from_str(&json).unwrap();
+        assert_eq!(t.validate(), Ok(()));
+        assert_eq!(t.iter().copied().collect::<Vec<_>>(), expected);
+
+        let t: Tree<i32, 4> = serde_json::from_str("[]").unwrap();
+        assert!(t.is_empty());
+    }
+
+    #[test]
+    fn serde_rejects_unsorted() {
+        let err = serde_json::from_str::<Tree<i32, 3>>("[1, 2, 4, 3]").unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("value at index 3 is not greater than the previous one"));
+        let err = serde_json::from_str::<Tree<i32, 3>>("[1, 2, 2]").unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("value at index 2 is not greater than the previous one"));
+        assert!(serde_json::from_str::<Tree<i32, 3>>("[1, 2, \"3\"]").is_err());
+    }
+}
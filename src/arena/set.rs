@@ -0,0 +1,434 @@
+use core::cmp::{max, min, Ordering};
+use core::iter::{FusedIterator, Peekable};
+
+use super::{Compare, Iter, Natural, Summary, Tree};
+
+/// When one tree is this many times smaller than the other, its values are looked up
+/// in the larger one by descending from the root, instead of merging both trees.
+const SEARCH_RATIO: usize = 16;
+
+/// Walks two trees in step, yielding the smaller head of each or both if they are equal.
+/// The heads are compared by the comparator of the first tree.
+#[derive(Debug)]
+struct MergeIter<'a, T, const M: usize, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    a: Peekable<Iter<'a, T, M, S, C>>,
+    b: Peekable<Iter<'a, T, M, S, C>>,
+    cmp: &'a C,
+}
+
+impl<'a, T, const M: usize, S, C> MergeIter<'a, T, M, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    fn new(a: &'a Tree<T, M, S, C>, b: &'a Tree<T, M, S, C>) -> Self {
+        MergeIter {
+            a: a.iter().peekable(),
+            b: b.iter().peekable(),
+            cmp: &a.cmp,
+        }
+    }
+
+    fn next(&mut self) -> (Option<&'a T>, Option<&'a T>) {
+        let ordering = match (self.a.peek(), self.b.peek()) {
+            (None, None) => return (None, None),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(a), Some(b)) => self.cmp.compare(a, b),
+        };
+        match ordering {
+            Ordering::Less => (self.a.next(), None),
+            Ordering::Greater => (None, self.b.next()),
+            Ordering::Equal => (self.a.next(), self.b.next()),
+        }
+    }
+
+    fn lens(&self) -> (usize, usize) {
+        (self.a.len(), self.b.len())
+    }
+}
+
+/// A lazy iterator over the values in either of two trees, in ascending order.
+///
+/// Created by [`Tree::union`].
+#[derive(Debug)]
+pub struct Union<'a, T, const M: usize, S = (), C = Natural>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    merge: MergeIter<'a, T, M, S, C>,
+}
+
+impl<'a, T, const M: usize, S, C> Iterator for Union<'a, T, M, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (a, b) = self.merge.next();
+        a.or(b)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (a, b) = self.merge.lens();
+        (max(a, b), Some(a + b))
+    }
+}
+
+impl<'a, T, const M: usize, S, C> FusedIterator for Union<'a, T, M, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+}
+
+/// A lazy iterator over the values in exactly one of two trees, in ascending order.
+///
+/// Created by [`Tree::symmetric_difference`].
+#[derive(Debug)]
+pub struct SymmetricDifference<'a, T, const M: usize, S = (), C = Natural>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    merge: MergeIter<'a, T, M, S, C>,
+}
+
+impl<'a, T, const M: usize, S, C> Iterator for SymmetricDifference<'a, T, M, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.merge.next() {
+                (None, None) => return None,
+                (Some(a), None) => return Some(a),
+                (None, Some(b)) => return Some(b),
+                (Some(_), Some(_)) => continue,
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (a, b) = self.merge.lens();
+        (0, Some(a + b))
+    }
+}
+
+impl<'a, T, const M: usize, S, C> FusedIterator for SymmetricDifference<'a, T, M, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+}
+
+/// A lazy iterator over the values in both of two trees, in ascending order.
+///
+/// Created by [`Tree::intersection`].
+#[derive(Debug)]
+pub struct Intersection<'a, T, const M: usize, S = (), C = Natural>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    inner: IntersectionInner<'a, T, M, S, C>,
+}
+
+#[derive(Debug)]
+enum IntersectionInner<'a, T, const M: usize, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    Stitch {
+        a: Iter<'a, T, M, S, C>,
+        b: Iter<'a, T, M, S, C>,
+        cmp: &'a C,
+    },
+    Search {
+        small: Iter<'a, T, M, S, C>,
+        large: &'a Tree<T, M, S, C>,
+    },
+}
+
+impl<'a, T, const M: usize, S, C> Iterator for Intersection<'a, T, M, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            IntersectionInner::Stitch { a, b, cmp } => {
+                let mut a_next = a.next()?;
+                let mut b_next = b.next()?;
+                loop {
+                    match cmp.compare(a_next, b_next) {
+                        Ordering::Less => a_next = a.next()?,
+                        Ordering::Greater => b_next = b.next()?,
+                        Ordering::Equal => return Some(a_next),
+                    }
+                }
+            }
+            IntersectionInner::Search { small, large } => {
+                small.find(|&value| large.get(value).is_some())
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.inner {
+            IntersectionInner::Stitch { a, b, .. } => (0, Some(min(a.len(), b.len()))),
+            IntersectionInner::Search { small, .. } => (0, Some(small.len())),
+        }
+    }
+}
+
+impl<'a, T, const M: usize, S, C> FusedIterator for Intersection<'a, T, M, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+}
+
+/// A lazy iterator over the values in one tree but not in the other, in ascending order.
+///
+/// Created by [`Tree::difference`].
+#[derive(Debug)]
+pub struct Difference<'a, T, const M: usize, S = (), C = Natural>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    inner: DifferenceInner<'a, T, M, S, C>,
+}
+
+#[derive(Debug)]
+enum DifferenceInner<'a, T, const M: usize, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    Stitch {
+        iter: Iter<'a, T, M, S, C>,
+        other: Peekable<Iter<'a, T, M, S, C>>,
+        cmp: &'a C,
+    },
+    Search {
+        iter: Iter<'a, T, M, S, C>,
+        other: &'a Tree<T, M, S, C>,
+    },
+}
+
+impl<'a, T, const M: usize, S, C> Iterator for Difference<'a, T, M, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            DifferenceInner::Stitch { iter, other, cmp } => 'values: loop {
+                let value = iter.next()?;
+                while let Some(&other_value) = other.peek() {
+                    match cmp.compare(other_value, value) {
+                        Ordering::Less => {
+                            other.next();
+                        }
+                        Ordering::Equal => continue 'values,
+                        Ordering::Greater => break,
+                    }
+                }
+                return Some(value);
+            },
+            DifferenceInner::Search { iter, other } => {
+                iter.find(|&value| other.get(value).is_none())
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (len, other_len) = match &self.inner {
+            DifferenceInner::Stitch { iter, other, .. } => (iter.len(), other.len()),
+            DifferenceInner::Search { iter, other } => (iter.len(), other.len()),
+        };
+        (len.saturating_sub(other_len), Some(len))
+    }
+}
+
+impl<'a, T, const M: usize, S, C> FusedIterator for Difference<'a, T, M, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+}
+
+impl<T, const M: usize, S, C> Tree<T, M, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    /// Visits the values in `self` or `other` without duplicates, in ascending order.
+    pub fn union<'a>(&'a self, other: &'a Self) -> Union<'a, T, M, S, C> {
+        Union {
+            merge: MergeIter::new(self, other),
+        }
+    }
+
+    /// Visits the values in both `self` and `other`, in ascending order.
+    ///
+    /// If one tree is much smaller, its values are looked up in the other one.
+    pub fn intersection<'a>(&'a self, other: &'a Self) -> Intersection<'a, T, M, S, C> {
+        let (small, large) = if self.len <= other.len {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let inner = if small.len * SEARCH_RATIO < large.len {
+            IntersectionInner::Search {
+                small: small.iter(),
+                large,
+            }
+        } else {
+            IntersectionInner::Stitch {
+                a: self.iter(),
+                b: other.iter(),
+                cmp: &self.cmp,
+            }
+        };
+        Intersection { inner }
+    }
+
+    /// Visits the values in `self` but not in `other`, in ascending order.
+    ///
+    /// If `self` is much smaller, its values are looked up in `other`.
+    pub fn difference<'a>(&'a self, other: &'a Self) -> Difference<'a, T, M, S, C> {
+        let inner = if self.len * SEARCH_RATIO < other.len {
+            DifferenceInner::Search {
+                iter: self.iter(),
+                other,
+            }
+        } else {
+            DifferenceInner::Stitch {
+                iter: self.iter(),
+                other: other.iter().peekable(),
+                cmp: &self.cmp,
+            }
+        };
+        Difference { inner }
+    }
+
+    /// Visits the values in `self` or `other` but not in both, in ascending order.
+    pub fn symmetric_difference<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> SymmetricDifference<'a, T, M, S, C> {
+        SymmetricDifference {
+            merge: MergeIter::new(self, other),
+        }
+    }
+
+    /// Returns `true` if all values in `self` are in `other` as well.
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.len <= other.len && self.difference(other).next().is_none()
+    }
+
+    /// Returns `true` if all values in `other` are in `self` as well.
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Returns `true` if `self` and `other` have no values in common.
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.intersection(other).next().is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    fn tree(vals: &[usize]) -> Tree<usize, 4> {
+        let mut t = Tree::default();
+        for &val in vals {
+            t.insert(val);
+        }
+        t
+    }
+
+    fn check(a: &[usize], b: &[usize]) {
+        let (ta, tb) = (tree(a), tree(b));
+        let sa: BTreeSet<_> = a.iter().copied().collect();
+        let sb: BTreeSet<_> = b.iter().copied().collect();
+
+        let expected: Vec<_> = sa.union(&sb).copied().collect();
+        assert_eq!(ta.union(&tb).copied().collect::<Vec<_>>(), expected);
+        let expected: Vec<_> = sa.intersection(&sb).copied().collect();
+        assert_eq!(ta.intersection(&tb).copied().collect::<Vec<_>>(), expected);
+        assert_eq!(tb.intersection(&ta).copied().collect::<Vec<_>>(), expected);
+        let expected: Vec<_> = sa.difference(&sb).copied().collect();
+        assert_eq!(ta.difference(&tb).copied().collect::<Vec<_>>(), expected);
+        let expected: Vec<_> = sb.difference(&sa).copied().collect();
+        assert_eq!(tb.difference(&ta).copied().collect::<Vec<_>>(), expected);
+        let expected: Vec<_> = sa.symmetric_difference(&sb).copied().collect();
+        assert_eq!(
+            ta.symmetric_difference(&tb).copied().collect::<Vec<_>>(),
+            expected
+        );
+
+        assert_eq!(ta.is_subset(&tb), sa.is_subset(&sb));
+        assert_eq!(tb.is_subset(&ta), sb.is_subset(&sa));
+        assert_eq!(ta.is_superset(&tb), sa.is_superset(&sb));
+        assert_eq!(ta.is_disjoint(&tb), sa.is_disjoint(&sb));
+    }
+
+    #[test]
+    fn set_operations() {
+        let shuffled: Vec<usize> = (0..1000).map(|v| v * 7919 % 1000).collect();
+        let evens: Vec<_> = shuffled.iter().map(|v| v * 2).collect();
+        let thirds: Vec<_> = shuffled.iter().map(|v| v * 3).collect();
+
+        check(&[], &[]);
+        check(&[1, 2, 3], &[]);
+        check(&evens, &thirds);
+        check(&evens, &shuffled);
+        check(&shuffled[..100], &shuffled);
+    }
+
+    #[test]
+    fn set_operations_search() {
+        let shuffled: Vec<usize> = (0..1000).map(|v| v * 7919 % 1000).collect();
+
+        // small enough to be looked up in the other tree
+        check(&[0, 10, 999, 1000, 5000], &shuffled);
+        check(&[1001, 1002], &shuffled);
+        check(&shuffled[..20], &shuffled);
+    }
+
+    #[test]
+    fn subset() {
+        let a = tree(&[2, 4, 6]);
+        let b = tree(&(0..10).collect::<Vec<_>>());
+        assert!(a.is_subset(&b));
+        assert!(!b.is_subset(&a));
+        assert!(b.is_superset(&a));
+        assert!(a.is_subset(&a));
+        assert!(tree(&[]).is_subset(&a));
+        assert!(!a.is_disjoint(&b));
+        assert!(a.is_disjoint(&tree(&[1, 3, 5, 7])));
+    }
+}
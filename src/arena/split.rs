@@ -0,0 +1,413 @@
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+
+use super::{Compare, Summary, Tree};
+
+impl<T, const M: usize, S, C> Tree<T, M, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    /// Splits the tree at the value, and returns a new tree holding
+    /// all the values greater than or equal to it.
+    ///
+    /// The nodes along the search path are cut into two, and the detached
+    /// subtrees on each side are joined back together by height.
+    /// The shorter part is moved into a new arena, counting its values on the way,
+    /// like `BTreeMap::split_off` does.
+    pub fn split_off<Q>(&mut self, value: &Q) -> Self
+    where
+        T: Borrow<Q>,
+        C: Clone,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        let len = self.len;
+        let (left, right) = self.split_node(self.root_id, value);
+        let height = |root: Option<usize>| root.map_or(0, |id| self.height(id) + 1);
+
+        let mut other = self.new_empty();
+        if height(left) < height(right) {
+            other.adopt_tree(self, left);
+            self.set_root(right, len - other.len);
+            core::mem::swap(self, &mut other);
+        } else {
+            other.adopt_tree(self, right);
+            self.set_root(left, len - other.len);
+        }
+        other
+    }
+
+    /// Moves all the values of `other` into the tree, leaving `other` empty.
+    ///
+    /// If all the values of one tree are less than the ones of the other,
+    /// the nodes of the smaller tree are moved into the arena of the larger one,
+    /// and its root is grafted onto the spine of the other at the matching height.
+    /// Moving the nodes from one arena to the other takes O(m) for the m values
+    /// of the smaller tree, so this is not the O(log n) join of a single arena.
+    ///
+    /// Otherwise the values of `other` are inserted one by one in O(m log n),
+    /// and the equal values already in the tree are kept.
+    pub fn append(&mut self, other: &mut Self)
+    where
+        C: Clone,
+    {
+        if other.is_empty() {
+            return;
+        }
+        if self.is_empty() {
+            core::mem::swap(self, other);
+            return;
+        }
+
+        let less = |a: Option<&T>, b: Option<&T>| self.less(a.unwrap(), b.unwrap());
+        let mut other_is_right = if less(self.last(), other.first()) {
+            true
+        } else if less(other.last(), self.first()) {
+            false
+        } else {
+            let empty = other.new_empty();
+            for value in core::mem::replace(other, empty) {
+                self.insert(value);
+            }
+            return;
+        };
+        if self.live_nodes() < other.live_nodes() {
+            core::mem::swap(self, other);
+            other_is_right = !other_is_right;
+        }
+
+        let len = self.len + other.len;
+        let separator = if other_is_right {
+            other.pop_first().unwrap()
+        } else {
+            other.pop_last().unwrap()
+        };
+        let grafted = if other.is_empty() {
+            None
+        } else {
+            Some(self.adopt(other, other.root_id))
+        };
+        *other = other.new_empty();
+
+        let root_id = Some(self.root_id);
+        if other_is_right {
+            self.join(root_id, separator, grafted);
+        } else {
+            self.join(grafted, separator, root_id);
+        }
+        self.len = len;
+    }
+
+    fn live_nodes(&self) -> usize {
+        self.arena.len() - self.free.len()
+    }
+
+    fn height(&self, node_id: usize) -> usize {
+        self.most_left(node_id).1
+    }
+
+    /// Makes the detached subtree holding `len` values the root,
+    /// or an empty leaf if it's missing.
+    fn set_root(&mut self, root_id: Option<usize>, len: usize) {
+        self.root_id = root_id.unwrap_or_else(|| self.new_node());
+        self.arena[self.root_id].parent = None;
+        self.len = len;
+    }
+
+    /// Replaces the empty tree with the detached subtree of `src`.
+    fn adopt_tree(&mut self, src: &mut Self, root_id: Option<usize>) {
+        if let Some(root_id) = root_id {
+            // the empty root is reused as the new root
+            self.free_node(self.root_id);
+            self.len = 0;
+            self.root_id = self.adopt(src, root_id);
+        }
+    }
+
+    /// Moves the subtree of `src` into the arena recursively,
+    /// freeing its nodes in `src` and adding its values to `len`.
+    /// Returns the new index of the subtree root.
+    fn adopt(&mut self, src: &mut Self, node_id: usize) -> usize {
+        let new_id = self.new_node();
+        let src_node = &mut src.arena[node_id];
+        let values = src_node.values.take();
+        let children = src_node.children.take();
+        let summary = core::mem::replace(&mut src_node.summary, S::empty());
+        src.free_node(node_id);
+
+        self.len += values.len();
+        let node = &mut self.arena[new_id];
+        node.values = values;
+        node.summary = summary;
+        for child_id in children {
+            let child_id = self.adopt(src, child_id);
+            self.arena[child_id].parent = Some(new_id);
+            self.arena[new_id].children.push(child_id);
+        }
+        new_id
+    }
+
+    /// Cuts the subtree into the detached subtrees of the values less than the given one,
+    /// and the rest. A missing subtree means no values lie on that side.
+    fn split_node<Q>(&mut self, node_id: usize, value: &Q) -> (Option<usize>, Option<usize>)
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        let node = &mut self.arena[node_id];
+        node.parent = None;
+        let (idx, found) = Self::binary_search(&self.cmp, &node.values, value);
+
+        if node.is_leaf() {
+            let right_values: Vec<T> = node.values.drain(idx..).collect();
+            let right = if right_values.is_empty() {
+                None
+            } else {
+                let right_id = self.new_node();
+                self.arena[right_id].values.extend(right_values);
+                self.update_summary(right_id);
+                Some(right_id)
+            };
+            let left = if self.arena[node_id].values.is_empty() {
+                self.free_node(node_id);
+                None
+            } else {
+                self.update_summary(node_id);
+                Some(node_id)
+            };
+            return (left, right);
+        }
+
+        // the child straddles the value, unless the value is found on its right
+        let child_id = node.children[idx];
+        let (child_left, child_right) = if found {
+            self.arena[child_id].parent = None;
+            (Some(child_id), None)
+        } else {
+            self.split_node(child_id, value)
+        };
+
+        // values: | left | right |, children: | left | child | right |
+        let node = &mut self.arena[node_id];
+        let mut right_values: Vec<T> = node.values.drain(idx..).collect();
+        let right_children: Vec<usize> = node.children.drain(idx + 1..).collect();
+        node.children.pop();
+
+        let left = match node.values.pop() {
+            Some(separator) => {
+                let rest = self.trim_node(node_id);
+                Some(self.join(Some(rest), separator, child_left))
+            }
+            None => {
+                self.free_node(node_id);
+                child_left
+            }
+        };
+        let right = if right_values.is_empty() {
+            child_right
+        } else {
+            let separator = right_values.remove(0);
+            let rest_id = self.new_node();
+            self.arena[rest_id].values.extend(right_values);
+            for &child_id in right_children.iter() {
+                self.arena[child_id].parent = Some(rest_id);
+            }
+            self.arena[rest_id].children.extend(right_children);
+            let rest = self.trim_node(rest_id);
+            Some(self.join(child_right, separator, Some(rest)))
+        };
+        (left, right)
+    }
+
+    /// Turns the internal node which lost some of its values and children
+    /// into a detached subtree. A node left with a single child is replaced by it.
+    fn trim_node(&mut self, node_id: usize) -> usize {
+        let node = &self.arena[node_id];
+        if node.values.is_empty() {
+            let child_id = node.children[0];
+            self.arena[child_id].parent = None;
+            self.free_node(node_id);
+            return child_id;
+        }
+        self.update_summary(node_id);
+        node_id
+    }
+
+    /// Concatenates two detached subtrees with a separator lying between them,
+    /// and returns the root of the joined tree, which becomes the root of the tree.
+    ///
+    /// The shorter subtree is grafted onto the spine of the taller one at the same height,
+    /// then the separator goes up through splitting,
+    /// and the grafted root is rebalanced by rotating or merging.
+    /// A missing subtree means the separator is put into the last leaf of the left one
+    /// or the first leaf of the right one. The callers set `len` once they are done.
+    fn join(&mut self, left: Option<usize>, separator: T, right: Option<usize>) -> usize {
+        let (left_id, right_id) = match (left, right) {
+            (Some(left_id), Some(right_id)) => (left_id, right_id),
+            (Some(root_id), None) => {
+                self.root_id = root_id;
+                let (leaf_id, _) = self.most_right(root_id);
+                let idx = self.arena[leaf_id].values.len();
+                self.insert_at(leaf_id, idx, separator, None);
+                return self.root_id;
+            }
+            (None, Some(root_id)) => {
+                self.root_id = root_id;
+                let (leaf_id, _) = self.most_left(root_id);
+                self.insert_at(leaf_id, 0, separator, None);
+                return self.root_id;
+            }
+            (None, None) => {
+                self.root_id = self.new_node();
+                self.insert_at(self.root_id, 0, separator, None);
+                return self.root_id;
+            }
+        };
+
+        let (left_height, right_height) = (self.height(left_id), self.height(right_id));
+        match left_height.cmp(&right_height) {
+            Ordering::Equal => {
+                self.root_id = left_id;
+                self.new_root(separator, right_id);
+                self.refill(left_id);
+                self.refill(right_id);
+            }
+            Ordering::Greater => {
+                self.root_id = left_id;
+                let mut node_id = left_id;
+                for _ in right_height + 1..left_height {
+                    node_id = *self.arena[node_id].children.last().unwrap();
+                }
+                let idx = self.arena[node_id].values.len();
+                self.insert_at(node_id, idx, separator, Some(right_id));
+                self.refill(right_id);
+            }
+            Ordering::Less => {
+                self.root_id = right_id;
+                let mut node_id = right_id;
+                for _ in left_height + 1..right_height {
+                    node_id = self.arena[node_id].children[0];
+                }
+                // the first child goes right of the separator
+                let first_id = core::mem::replace(&mut self.arena[node_id].children[0], left_id);
+                self.arena[left_id].parent = Some(node_id);
+                self.insert_at(node_id, 0, separator, Some(first_id));
+                self.refill(left_id);
+            }
+        }
+        self.root_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree<const M: usize>(vals: impl Iterator<Item = usize>) -> Tree<usize, M> {
+        let mut t = Tree::default();
+        for val in vals {
+            t.insert(val);
+        }
+        t
+    }
+
+    fn check<const M: usize>(t: &Tree<usize, M>, expected: impl Iterator<Item = usize>) {
+        assert_eq!(t.validate(), Ok(()));
+        assert_eq!(
+            t.iter().copied().collect::<Vec<_>>(),
+            expected.collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn split_off() {
+        for n in [0, 1, 2, 10, 100, 500] {
+            for at in [0, 1, n / 3, n / 2, n - n.min(1), n, n + 1] {
+                let mut t = tree::<3>((0..n).map(|v| v * 7919 % n.max(1)));
+                let other = t.split_off(&at);
+                check(&t, 0..at.min(n));
+                check(&other, at.min(n)..n);
+
+                let mut t = tree::<6>((0..n).map(|v| v * 2));
+                let other = t.split_off(&(at * 2 + 1));
+                check(&t, (0..n).map(|v| v * 2).filter(|&v| v <= at * 2));
+                check(&other, (0..n).map(|v| v * 2).filter(|&v| v > at * 2));
+            }
+        }
+    }
+
+    #[test]
+    fn split_off_reuse() {
+        let mut t = tree::<4>(0..1000);
+        let mut other = t.split_off(&300);
+        let rest = other.split_off(&700);
+        check(&t, 0..300);
+        check(&other, 300..700);
+        check(&rest, 700..1000);
+
+        for val in 300..700 {
+            t.insert(val);
+            other.delete(&val);
+        }
+        check(&t, 0..700);
+        check(&other, 0..0);
+    }
+
+    #[test]
+    fn append() {
+        for (a, b) in [
+            (0, 0),
+            (0, 5),
+            (1, 1),
+            (1, 100),
+            (100, 1),
+            (30, 700),
+            (500, 500),
+        ] {
+            let mut left = tree::<3>(0..a);
+            let mut right = tree::<3>(a..a + b);
+            left.append(&mut right);
+            check(&left, 0..a + b);
+            check(&right, 0..0);
+
+            let mut left = tree::<5>(0..a);
+            let mut right = tree::<5>(a..a + b);
+            right.append(&mut left);
+            check(&right, 0..a + b);
+            check(&left, 0..0);
+        }
+    }
+
+    #[test]
+    fn append_overlapping() {
+        let mut t = tree::<4>((0..100).map(|v| v * 2));
+        let mut other = tree::<4>((0..100).map(|v| v * 3));
+        t.append(&mut other);
+        check(
+            &t,
+            (0..300).filter(|v| v % 2 == 0 && v / 2 < 100 || v % 3 == 0),
+        );
+        check(&other, 0..0);
+    }
+
+    #[test]
+    fn split_off_append() {
+        let vals: Vec<usize> = (0..2000).map(|v| v * 7919 % 2000).collect();
+        let mut t = tree::<8>(vals.iter().copied());
+        for &at in vals.iter().take(50) {
+            let mut other = t.split_off(&at);
+            assert_eq!(t.len() + other.len(), 2000);
+            if at % 2 == 0 {
+                t.append(&mut other);
+                check(&t, 0..2000);
+            } else {
+                other.append(&mut t);
+                check(&other, 0..2000);
+                t = other;
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
+
+use arrayvec::ArrayVec;
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Growable list of nodes or node indexes backing a tree.
+///
+/// This trait is sealed, it's implemented for `Vec` and `ArrayVec` only.
+pub trait Buffer<X>: private::Sealed + Default + Deref<Target = [X]> + DerefMut {
+    /// Appends a value to the back of the list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the list is fixed-size and full.
+    fn push(&mut self, value: X);
+    fn pop(&mut self) -> Option<X>;
+    fn truncate(&mut self, len: usize);
+    fn clear(&mut self);
+}
+
+/// Where a tree keeps its arena of nodes and its free list.
+///
+/// This trait is sealed, it's implemented for [`Heap`] and [`Fixed`] only.
+/// A tree on [`Fixed`] storage can't insert on its own, as running out of nodes
+/// in the middle of a split would break it, so it's built through [`StaticTree`].
+///
+/// [`StaticTree`]: super::StaticTree
+pub trait Storage: private::Sealed {
+    type Buf<X>: Buffer<X>;
+}
+
+/// Storage on the heap, growing as the tree needs more nodes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Heap;
+
+/// Storage for at most `N` nodes kept inline in the tree, which never allocates.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Fixed<const N: usize>;
+
+impl private::Sealed for Heap {}
+
+impl<const N: usize> private::Sealed for Fixed<N> {}
+
+impl<X> private::Sealed for Vec<X> {}
+
+impl<X, const N: usize> private::Sealed for ArrayVec<X, N> {}
+
+impl Storage for Heap {
+    type Buf<X> = Vec<X>;
+}
+
+impl<const N: usize> Storage for Fixed<N> {
+    type Buf<X> = ArrayVec<X, N>;
+}
+
+impl<X> Buffer<X> for Vec<X> {
+    fn push(&mut self, value: X) {
+        Vec::push(self, value)
+    }
+
+    fn pop(&mut self) -> Option<X> {
+        Vec::pop(self)
+    }
+
+    fn truncate(&mut self, len: usize) {
+        Vec::truncate(self, len)
+    }
+
+    fn clear(&mut self) {
+        Vec::clear(self)
+    }
+}
+
+impl<X, const N: usize> Buffer<X> for ArrayVec<X, N> {
+    fn push(&mut self, value: X) {
+        ArrayVec::push(self, value)
+    }
+
+    fn pop(&mut self) -> Option<X> {
+        ArrayVec::pop(self)
+    }
+
+    fn truncate(&mut self, len: usize) {
+        ArrayVec::truncate(self, len)
+    }
+
+    fn clear(&mut self) {
+        ArrayVec::clear(self)
+    }
+}
@@ -0,0 +1,290 @@
+use core::borrow::Borrow;
+use core::ops::{Bound, RangeBounds};
+
+use super::{Compare, Tree};
+
+/// An aggregate of values which every node caches for its subtree.
+///
+/// `combine` must be associative and `empty` must be its identity,
+/// since the tree combines the summaries in key order but in any grouping.
+pub trait Summary<T>: Clone {
+    fn empty() -> Self;
+
+    fn from_value(value: &T) -> Self;
+
+    fn combine(&self, other: &Self) -> Self;
+}
+
+impl<T> Summary<T> for () {
+    fn empty() -> Self {}
+
+    fn from_value(_: &T) -> Self {}
+
+    fn combine(&self, _: &Self) -> Self {}
+}
+
+/// Combines two summaries side by side, like `(Size, Sum)`.
+impl<T, A, B> Summary<T> for (A, B)
+where
+    A: Summary<T>,
+    B: Summary<T>,
+{
+    fn empty() -> Self {
+        (A::empty(), B::empty())
+    }
+
+    fn from_value(value: &T) -> Self {
+        (A::from_value(value), B::from_value(value))
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        (self.0.combine(&other.0), self.1.combine(&other.1))
+    }
+}
+
+/// A summary which knows the number of values in its subtree.
+/// It enables the order statistics like [`Tree::rank`] and [`Tree::select`].
+pub trait SubtreeSize {
+    fn size(&self) -> usize;
+}
+
+/// The number of values in a subtree.
+///
+/// It's opt-in, so the nodes of a plain tree don't have to keep their size up to date.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Size(pub usize);
+
+impl<T> Summary<T> for Size {
+    fn empty() -> Self {
+        Size(0)
+    }
+
+    fn from_value(_: &T) -> Self {
+        Size(1)
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        Size(self.0 + other.0)
+    }
+}
+
+impl SubtreeSize for Size {
+    fn size(&self) -> usize {
+        self.0
+    }
+}
+
+/// The size is taken from the first summary of the pair.
+impl<A, B> SubtreeSize for (A, B)
+where
+    A: SubtreeSize,
+{
+    fn size(&self) -> usize {
+        self.0.size()
+    }
+}
+
+impl<T, const M: usize, S, C> Tree<T, M, S, C>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+{
+    /// Returns the summary of all values in the tree.
+    pub fn summary(&self) -> &S {
+        &self.arena[self.root_id].summary
+    }
+
+    /// Combines the summaries of the values in the range, in ascending order.
+    ///
+    /// It visits at most two paths from the root and takes the cached summaries
+    /// of the subtrees lying in the range entirely.
+    pub fn fold_range<K, R>(&self, range: R) -> S
+    where
+        T: Borrow<K>,
+        C: Compare<K>,
+        K: ?Sized,
+        R: RangeBounds<K>,
+    {
+        self.fold_node(self.root_id, range.start_bound(), range.end_bound())
+    }
+
+    fn fold_node<K>(&self, node_id: usize, lower: Bound<&K>, upper: Bound<&K>) -> S
+    where
+        T: Borrow<K>,
+        C: Compare<K>,
+        K: ?Sized,
+    {
+        let node = &self.arena[node_id];
+        if let (Bound::Unbounded, Bound::Unbounded) = (lower, upper) {
+            return node.summary.clone();
+        }
+
+        // values[start..end] are in the range
+        let start = match lower {
+            Bound::Included(value) => Self::binary_search(&self.cmp, &node.values, value).0,
+            Bound::Excluded(value) => match Self::binary_search(&self.cmp, &node.values, value) {
+                (idx, true) => idx + 1,
+                (idx, false) => idx,
+            },
+            Bound::Unbounded => 0,
+        };
+        let end = match upper {
+            Bound::Included(value) => match Self::binary_search(&self.cmp, &node.values, value) {
+                (idx, true) => idx + 1,
+                (idx, false) => idx,
+            },
+            Bound::Excluded(value) => Self::binary_search(&self.cmp, &node.values, value).0,
+            Bound::Unbounded => node.values.len(),
+        };
+
+        if start >= end {
+            return match node.children.get(start) {
+                Some(&child_id) if start == end => self.fold_node(child_id, lower, upper),
+                _ => S::empty(),
+            };
+        }
+
+        let mut summary = match node.children.get(start) {
+            Some(&child_id) => self.fold_node(child_id, lower, Bound::Unbounded),
+            None => S::empty(),
+        };
+        for idx in start..end {
+            summary = summary.combine(&S::from_value(&node.values[idx]));
+            if idx + 1 < end {
+                if let Some(&child_id) = node.children.get(idx + 1) {
+                    summary = summary.combine(&self.arena[child_id].summary);
+                }
+            }
+        }
+        if let Some(&child_id) = node.children.get(end) {
+            summary = summary.combine(&self.fold_node(child_id, Bound::Unbounded, upper));
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Sum(i64);
+
+    impl Summary<i64> for Sum {
+        fn empty() -> Self {
+            Sum(0)
+        }
+
+        fn from_value(value: &i64) -> Self {
+            Sum(*value)
+        }
+
+        fn combine(&self, other: &Self) -> Self {
+            Sum(self.0 + other.0)
+        }
+    }
+
+    /// Keeps the first and last values, which is sensitive to the combining order.
+    #[derive(Debug, Clone, PartialEq)]
+    struct Ends(Option<(i64, i64)>);
+
+    impl Summary<i64> for Ends {
+        fn empty() -> Self {
+            Ends(None)
+        }
+
+        fn from_value(value: &i64) -> Self {
+            Ends(Some((*value, *value)))
+        }
+
+        fn combine(&self, other: &Self) -> Self {
+            match (self.0, other.0) {
+                (Some((first, _)), Some((_, last))) => Ends(Some((first, last))),
+                (ends, None) | (None, ends) => Ends(ends),
+            }
+        }
+    }
+
+    fn check_sum(t: &Tree<i64, 4, Sum>, vals: &[i64]) {
+        assert_eq!(t.summary(), &Sum(vals.iter().sum()));
+        for lower in (-5..105).step_by(7) {
+            for upper in (lower..110).step_by(11) {
+                let expected = vals.iter().filter(|&&v| lower <= v && v < upper).sum();
+                assert_eq!(t.fold_range(lower..upper), Sum(expected));
+                let expected = vals.iter().filter(|&&v| lower < v && v <= upper).sum();
+                assert_eq!(
+                    t.fold_range((Bound::Excluded(lower), Bound::Included(upper))),
+                    Sum(expected)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fold_range_sum() {
+        let mut t = Tree::<i64, 4, Sum>::default();
+        assert_eq!(t.fold_range(..), Sum(0));
+
+        let mut vals: Vec<i64> = (0..100).map(|v| v * 7919 % 100).collect();
+        for &val in vals.iter() {
+            t.insert(val);
+        }
+        check_sum(&t, &vals);
+
+        for val in (0..100).filter(|v| v % 3 == 0) {
+            t.delete(&val);
+        }
+        vals.retain(|v| v % 3 != 0);
+        check_sum(&t, &vals);
+
+        for val in 0..100 {
+            t.delete(&val);
+        }
+        assert_eq!(t.summary(), &Sum(0));
+    }
+
+    #[test]
+    fn fold_range_try_insert() {
+        // try_insert puts the value straight into the leaf and walks up afterwards
+        let mut t = Tree::<i64, 4, Sum>::default();
+        let vals: Vec<i64> = (0..100).map(|v| v * 7919 % 100).collect();
+        for &val in vals.iter() {
+            assert_eq!(t.try_insert(val), Ok(true));
+        }
+        check_sum(&t, &vals);
+    }
+
+    #[test]
+    fn fold_range_in_order() {
+        let mut t = Tree::<i64, 3, Ends>::default();
+        for val in (0..200).rev() {
+            t.insert(val);
+        }
+        for val in (0..200).filter(|v| v % 5 == 1) {
+            t.delete(&val);
+        }
+
+        assert_eq!(t.summary(), &Ends(Some((0, 199))));
+        assert_eq!(t.fold_range(10..100), Ends(Some((10, 99))));
+        assert_eq!(t.fold_range(11..=101), Ends(Some((12, 100))));
+        assert_eq!(t.fold_range(..=1), Ends(Some((0, 0))));
+        assert_eq!(t.fold_range(1..2), Ends(None));
+    }
+
+    #[test]
+    fn size_with_sum() {
+        let mut t = Tree::<i64, 4, (Size, Sum)>::default();
+        for val in (0..100).map(|v| v * 7919 % 100) {
+            t.insert(val);
+        }
+        for val in (0..100).filter(|v| v % 4 == 0) {
+            t.delete(&val);
+        }
+
+        let vals: Vec<i64> = (0..100).filter(|v| v % 4 != 0).collect();
+        assert_eq!(t.summary(), &(Size(75), Sum(vals.iter().sum())));
+        assert_eq!(t.rank(&50), 37);
+        assert_eq!(t.select(37), Some(&50));
+        assert_eq!(t.fold_range(10..20), (Size(8), Sum(117)));
+    }
+}
@@ -0,0 +1,250 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::{Compare, Storage, Summary, Tree};
+
+/// The structural rules of the tree checked by [`Tree::validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Invariant {
+    /// Values in a node are not in strictly ascending order.
+    ValueOrder,
+    /// A value lies outside the range given by the separators of its ancestors.
+    SeparatorOrder,
+    /// An internal node doesn't have exactly one more child than values.
+    ChildCount,
+    /// A leaf is not at the same depth as the other leaves.
+    LeafDepth,
+    /// A non-root node holds fewer than `(M - 1) / 2` values.
+    MinOccupancy,
+    /// A node holds more than `M - 1` values.
+    MaxOccupancy,
+    /// The `idx` of a node doesn't match its position in the arena,
+    /// or a child index points outside of the arena.
+    Index,
+    /// The `parent` of a node doesn't point to the node holding it as a child.
+    ParentLink,
+    /// The element count of the tree doesn't match the values stored in it.
+    Length,
+}
+
+impl fmt::Display for Invariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Invariant::ValueOrder => "values are not in ascending order",
+            Invariant::SeparatorOrder => "value lies outside of its separators",
+            Invariant::ChildCount => "children count is not values count plus one",
+            Invariant::LeafDepth => "leaf depth is not uniform",
+            Invariant::MinOccupancy => "node holds too few values",
+            Invariant::MaxOccupancy => "node holds too many values",
+            Invariant::Index => "node index doesn't match the arena",
+            Invariant::ParentLink => "parent link is broken",
+            Invariant::Length => "element count doesn't match",
+        };
+        f.write_str(msg)
+    }
+}
+
+/// Describes which node of the tree breaks which rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvariantViolation {
+    pub node_id: usize,
+    pub invariant: Invariant,
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "node #{}: {}", self.node_id, self.invariant)
+    }
+}
+
+impl core::error::Error for InvariantViolation {}
+
+struct Validation<'a, T> {
+    visited: Vec<bool>,
+    leaf_depth: Option<usize>,
+    len: usize,
+    lower: Option<&'a T>,
+}
+
+impl<T, const M: usize, S, C, A> Tree<T, M, S, C, A>
+where
+    C: Compare<T>,
+    S: Summary<T>,
+    A: Storage,
+{
+    /// Walks the whole tree from the root and checks its structural invariants.
+    /// Returns the first violation found.
+    pub fn validate(&self) -> Result<(), InvariantViolation> {
+        let violation = |node_id, invariant| InvariantViolation { node_id, invariant };
+        if self.root_id >= self.arena.len() {
+            return Err(violation(self.root_id, Invariant::Index));
+        }
+        if self.arena[self.root_id].parent.is_some() {
+            return Err(violation(self.root_id, Invariant::ParentLink));
+        }
+
+        let mut state = Validation {
+            visited: vec![false; self.arena.len()],
+            leaf_depth: None,
+            len: 0,
+            lower: None,
+        };
+        self.validate_node(self.root_id, 0, None, &mut state)
+            .map_err(|invariant| violation(invariant.0, invariant.1))?;
+
+        if state.len != self.len {
+            return Err(violation(self.root_id, Invariant::Length));
+        }
+        Ok(())
+    }
+
+    /// Checks the subtree in order. Values must be greater than `state.lower`,
+    /// which is the last value visited, and less than `upper`.
+    fn validate_node<'a>(
+        &'a self,
+        node_id: usize,
+        depth: usize,
+        upper: Option<&'a T>,
+        state: &mut Validation<'a, T>,
+    ) -> Result<(), (usize, Invariant)> {
+        if state.visited[node_id] {
+            return Err((node_id, Invariant::ParentLink));
+        }
+        state.visited[node_id] = true;
+
+        let node = &self.arena[node_id];
+        if node.idx != node_id {
+            return Err((node_id, Invariant::Index));
+        }
+        if !node.is_root() && node.values.len() < (M - 1) / 2 {
+            return Err((node_id, Invariant::MinOccupancy));
+        }
+        if node.values.len() > M - 1 {
+            return Err((node_id, Invariant::MaxOccupancy));
+        }
+        if node.values.windows(2).any(|w| !self.less(&w[0], &w[1])) {
+            return Err((node_id, Invariant::ValueOrder));
+        }
+        let out_of_range = |value: &T| {
+            state.lower.is_some_and(|lower| !self.less(lower, value))
+                || upper.is_some_and(|upper| !self.less(value, upper))
+        };
+        if node.values.iter().any(out_of_range) {
+            return Err((node_id, Invariant::SeparatorOrder));
+        }
+        state.len += node.values.len();
+
+        if node.is_leaf() {
+            match state.leaf_depth {
+                Some(leaf_depth) if leaf_depth != depth => {
+                    return Err((node_id, Invariant::LeafDepth))
+                }
+                _ => state.leaf_depth = Some(depth),
+            }
+            if let Some(last) = node.values.last() {
+                state.lower = Some(last);
+            }
+            return Ok(());
+        }
+
+        if node.children.len() != node.values.len() + 1 {
+            return Err((node_id, Invariant::ChildCount));
+        }
+        for (idx, &child_id) in node.children.iter().enumerate() {
+            if child_id >= self.arena.len() {
+                return Err((node_id, Invariant::Index));
+            }
+            if self.arena[child_id].parent != Some(node_id) {
+                return Err((child_id, Invariant::ParentLink));
+            }
+            let separator = node.values.get(idx);
+            self.validate_node(child_id, depth + 1, separator.or(upper), state)?;
+            if separator.is_some() {
+                state.lower = separator;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn validate_ok() {
+    let mut t = Tree::<_, 4>::default();
+    assert_eq!(t.validate(), Ok(()));
+    for val in (0..300).map(|v| v * 7919 % 300) {
+        t.insert(val);
+        assert_eq!(t.validate(), Ok(()));
+    }
+    for val in (0..300).filter(|v| v % 3 != 0) {
+        t.delete(&val);
+        assert_eq!(t.validate(), Ok(()));
+    }
+    t.compact();
+    assert_eq!(t.validate(), Ok(()));
+}
+
+#[test]
+fn validate_violations() {
+    fn tree() -> Tree<i32, 3> {
+        let mut t = Tree::default();
+        for val in 1..8 {
+            t.insert(val);
+        }
+        // #6[4]
+        // #2[2] #5[6]
+        // #0[1] #1[3] #3[5] #4[7]
+        t
+    }
+    let violation = |node_id, invariant| Err(InvariantViolation { node_id, invariant });
+
+    let mut t = tree();
+    t.arena[0].values.push(0);
+    assert_eq!(t.validate(), violation(0, Invariant::ValueOrder));
+
+    let mut t = tree();
+    t.arena[1].values[0] = 5;
+    assert_eq!(t.validate(), violation(1, Invariant::SeparatorOrder));
+
+    let mut t = tree();
+    t.arena[5].values[0] = 3;
+    assert_eq!(t.validate(), violation(5, Invariant::SeparatorOrder));
+
+    let mut t = tree();
+    t.arena[3].values.clear();
+    assert_eq!(t.validate(), violation(3, Invariant::MinOccupancy));
+
+    let mut t = tree();
+    t.arena[3].values.extend([5, 5]);
+    assert_eq!(t.validate(), violation(3, Invariant::MaxOccupancy));
+
+    let mut t = tree();
+    t.arena[2].children.pop();
+    assert_eq!(t.validate(), violation(2, Invariant::ChildCount));
+
+    let mut t = tree();
+    t.arena[4].parent = Some(2);
+    assert_eq!(t.validate(), violation(4, Invariant::ParentLink));
+
+    let mut t = tree();
+    t.arena[3].idx = 4;
+    assert_eq!(t.validate(), violation(3, Invariant::Index));
+
+    let mut t = tree();
+    t.len += 1;
+    assert_eq!(t.validate(), violation(6, Invariant::Length));
+
+    let mut t = Tree::<i32, 3>::default();
+    for val in [10, 20, 30] {
+        t.insert(val);
+    }
+    // #2[20]
+    // #0[10] #1[30]
+    for val in [25, 35] {
+        let leaf_id = t.new_node();
+        t.arena[leaf_id].values.push(val);
+        t.arena[leaf_id].parent = Some(1);
+        t.arena[1].children.push(leaf_id);
+    }
+    assert_eq!(t.validate(), violation(3, Invariant::LeafDepth));
+}
@@ -1,12 +1,13 @@
-#![allow(incomplete_features)]
-#![feature(const_default_impls)]
-#![feature(generic_const_exprs)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
-#[cfg(debug_assertions)]
+extern crate alloc;
+
+#[cfg(all(feature = "std", debug_assertions))]
 #[macro_use]
 extern crate std;
 
-#[cfg(debug_assertions)]
+#[cfg(all(feature = "std", debug_assertions))]
+#[allow(unused_macros)]
 macro_rules! debug {
 	() => {
 		dbg!()
@@ -19,7 +20,8 @@ macro_rules! debug {
 	};
 }
 
-#[cfg(not(debug_assertions))]
+#[cfg(not(all(feature = "std", debug_assertions)))]
+#[allow(unused_macros)]
 macro_rules! debug {
 	() => {};
 	($val:expr $(,)?) => {{
@@ -32,3 +34,4 @@ macro_rules! debug {
 }
 
 pub mod arena;
+pub mod slab;
@@ -0,0 +1,725 @@
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::iter::{self, FusedIterator};
+
+use crate::arena::{Compare, Natural};
+
+/// The bookkeeping of a node, whose values and children live in the slabs of the tree.
+#[derive(Debug, Clone)]
+struct Node {
+    parent: Option<usize>,
+    /// The number of values in the node.
+    len: usize,
+    is_leaf: bool,
+}
+
+/// B-Tree whose order is chosen at construction instead of by a const generic.
+///
+/// The values and the children of every node are stored in two slabs shared by
+/// the whole tree, `order - 1` value slots and `order` child slots per node,
+/// so node `i` owns the `i`-th chunk of each slab.
+#[derive(Debug)]
+pub struct Tree<T, C = Natural> {
+    order: usize,
+    nodes: Vec<Node>,
+    values: Vec<Option<T>>,
+    children: Vec<usize>,
+    root_id: usize,
+    len: usize,
+    /// Indexes of the vacant nodes which can be reused.
+    free: Vec<usize>,
+    cmp: C,
+}
+
+impl<T, C> Tree<T, C>
+where
+    C: Compare<T> + Default,
+{
+    /// Creates an empty tree whose nodes have at most `order` children.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is less than 3.
+    pub fn with_order(order: usize) -> Self {
+        Self::with_order_and_comparator(order, C::default())
+    }
+}
+
+impl<T, C> Tree<T, C>
+where
+    C: Compare<T>,
+{
+    /// Creates an empty tree whose nodes have at most `order` children,
+    /// ordering its values by the comparator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is less than 3.
+    pub fn with_order_and_comparator(order: usize, cmp: C) -> Self {
+        assert!(order >= 3, "order must be at least 3");
+        let mut t = Tree {
+            order,
+            nodes: Vec::new(),
+            values: Vec::new(),
+            children: Vec::new(),
+            root_id: 0,
+            len: 0,
+            free: Vec::new(),
+            cmp,
+        };
+        t.root_id = t.new_node(true);
+        t
+    }
+
+    /// Returns the maximum number of children of a node.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Returns the number of values in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn capacity(&self) -> usize {
+        self.order - 1
+    }
+
+    fn min_values(&self) -> usize {
+        (self.order - 1) / 2
+    }
+
+    /// Takes a vacant node from the free list, or grows the slabs by one node.
+    fn new_node(&mut self, is_leaf: bool) -> usize {
+        let node = Node {
+            parent: None,
+            len: 0,
+            is_leaf,
+        };
+        if let Some(node_id) = self.free.pop() {
+            self.nodes[node_id] = node;
+            return node_id;
+        }
+        let node_id = self.nodes.len();
+        self.nodes.push(node);
+        let capacity = self.capacity();
+        self.values
+            .extend(iter::repeat_with(|| None).take(capacity));
+        self.children.extend(iter::repeat_n(0, self.order));
+        node_id
+    }
+
+    /// Puts the emptied node into the free list for reusing.
+    fn free_node(&mut self, node_id: usize) {
+        self.nodes[node_id].parent = None;
+        self.free.push(node_id);
+    }
+
+    /// Returns the index of the value slot in the slab.
+    fn slot(&self, (node_id, idx): (usize, usize)) -> usize {
+        node_id * self.capacity() + idx
+    }
+
+    fn value(&self, handle: (usize, usize)) -> &T {
+        self.values[self.slot(handle)].as_ref().unwrap()
+    }
+
+    fn child(&self, node_id: usize, idx: usize) -> usize {
+        self.children[node_id * self.order + idx]
+    }
+
+    /// Returns the position of the child among the children of its parent.
+    fn child_idx(&self, parent_id: usize, child_id: usize) -> usize {
+        let start = parent_id * self.order;
+        let len = self.nodes[parent_id].len;
+        self.children[start..=start + len]
+            .iter()
+            .position(|&id| id == child_id)
+            .unwrap()
+    }
+
+    /// Searches the values of the node with a comparator function, like `slice::binary_search_by`.
+    fn binary_search_by<F>(&self, node_id: usize, f: &F) -> (usize, bool)
+    where
+        F: Fn(&T) -> Ordering,
+    {
+        let start = self.slot((node_id, 0));
+        let values = &self.values[start..start + self.nodes[node_id].len];
+        match values.binary_search_by(|v| f(v.as_ref().unwrap())) {
+            Ok(idx) => (idx, true),
+            Err(idx) => (idx, false),
+        }
+    }
+
+    /// Descends from the root with the comparator function.
+    /// Returns the position of the equal value,
+    /// or the leaf and the index where it would be inserted.
+    fn search_by<F>(&self, f: &F) -> Result<(usize, usize), (usize, usize)>
+    where
+        F: Fn(&T) -> Ordering,
+    {
+        let mut cur = self.root_id;
+        loop {
+            let (idx, found) = self.binary_search_by(cur, f);
+            if found {
+                return Ok((cur, idx));
+            }
+            if self.nodes[cur].is_leaf {
+                return Err((cur, idx));
+            }
+            cur = self.child(cur, idx);
+        }
+    }
+
+    /// Returns a reference to the value equals to the given one.
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        self.search_by(&|v: &T| self.cmp.compare(v.borrow(), value))
+            .ok()
+            .map(|handle| self.value(handle))
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        self.get(value).is_some()
+    }
+
+    /// Adds a value to the tree.
+    /// Returns `false` if an equal value is in the tree already.
+    pub fn insert(&mut self, value: T) -> bool {
+        let (leaf_id, idx) = match self.search_by(&|v: &T| self.cmp.compare(v, &value)) {
+            Ok(_) => return false,
+            Err(position) => position,
+        };
+        self.insert_at(leaf_id, idx, value, None);
+        self.len += 1;
+        true
+    }
+
+    /// Inserts the value with its right child at the position of the node,
+    /// then splits the nodes upward while they overflow.
+    fn insert_at(&mut self, node_id: usize, idx: usize, value: T, right_id: Option<usize>) {
+        let (mut node_id, mut idx, mut value, mut right_id) = (node_id, idx, value, right_id);
+        loop {
+            if self.nodes[node_id].len < self.capacity() {
+                self.put(node_id, idx, value, idx + 1, right_id);
+                return;
+            }
+            let (median, new_id) = self.split(node_id, idx, value, right_id);
+            match self.nodes[node_id].parent {
+                Some(parent_id) => {
+                    idx = self.child_idx(parent_id, node_id);
+                    node_id = parent_id;
+                    value = median;
+                    right_id = Some(new_id);
+                }
+                None => {
+                    let root_id = self.new_node(false);
+                    self.put(root_id, 0, median, 0, Some(node_id));
+                    let start = root_id * self.order;
+                    self.children[start + 1] = new_id;
+                    self.nodes[new_id].parent = Some(root_id);
+                    self.root_id = root_id;
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Splits the full node while inserting the value with its right child.
+    /// Returns the median value and the new right node.
+    ///
+    /// The values and children after the median are moved into the new node in place,
+    /// then the value goes into the side it belongs to, unless it's the median itself.
+    fn split(
+        &mut self,
+        node_id: usize,
+        idx: usize,
+        value: T,
+        right_id: Option<usize>,
+    ) -> (T, usize) {
+        let is_leaf = self.nodes[node_id].is_leaf;
+        let new_id = self.new_node(is_leaf);
+        self.nodes[new_id].parent = self.nodes[node_id].parent;
+
+        // with the value, there are `order` values and the median is at `order / 2`
+        let capacity = self.capacity();
+        let mid = self.order / 2;
+        let (moved, moved_children) = match idx.cmp(&mid) {
+            Ordering::Greater => (mid + 1, mid + 1),
+            Ordering::Equal => (mid, mid + 1),
+            Ordering::Less => (mid, mid),
+        };
+
+        let (start, new_start) = (self.slot((node_id, 0)), self.slot((new_id, 0)));
+        for i in moved..capacity {
+            self.values.swap(start + i, new_start + i - moved);
+        }
+        self.nodes[node_id].len = moved;
+        self.nodes[new_id].len = capacity - moved;
+        if !is_leaf {
+            // the right child of the median value leads the new node
+            let offset = usize::from(idx == mid);
+            let (start, new_start) = (node_id * self.order, new_id * self.order);
+            for i in moved_children..self.order {
+                let child_id = self.children[start + i];
+                self.children[new_start + offset + i - moved_children] = child_id;
+                self.nodes[child_id].parent = Some(new_id);
+            }
+        }
+
+        let median = match idx.cmp(&mid) {
+            Ordering::Greater => {
+                let median = self.values[start + mid].take().unwrap();
+                self.nodes[node_id].len -= 1;
+                self.put(new_id, idx - mid - 1, value, idx - mid, right_id);
+                median
+            }
+            Ordering::Equal => {
+                if let Some(right_id) = right_id {
+                    self.children[new_id * self.order] = right_id;
+                    self.nodes[right_id].parent = Some(new_id);
+                }
+                value
+            }
+            Ordering::Less => {
+                let median = self.values[start + mid - 1].take().unwrap();
+                self.nodes[node_id].len -= 1;
+                self.put(node_id, idx, value, idx + 1, right_id);
+                median
+            }
+        };
+        (median, new_id)
+    }
+
+    /// Puts the value at `idx` and the child at `child_idx` of the node which has room,
+    /// shifting the following ones right.
+    fn put(
+        &mut self,
+        node_id: usize,
+        idx: usize,
+        value: T,
+        child_idx: usize,
+        child: Option<usize>,
+    ) {
+        let len = self.nodes[node_id].len;
+        let start = self.slot((node_id, 0));
+        self.values[start + idx..=start + len].rotate_right(1);
+        self.values[start + idx] = Some(value);
+        if let Some(child_id) = child {
+            let start = node_id * self.order;
+            self.children[start + child_idx..=start + len + 1].rotate_right(1);
+            self.children[start + child_idx] = child_id;
+            self.nodes[child_id].parent = Some(node_id);
+        }
+        self.nodes[node_id].len += 1;
+    }
+
+    /// Takes the value at `idx` and the child at `child_idx` out of the node,
+    /// shifting the following ones left.
+    fn take(&mut self, node_id: usize, idx: usize, child_idx: usize) -> (T, Option<usize>) {
+        let len = self.nodes[node_id].len;
+        let start = self.slot((node_id, 0));
+        let value = self.values[start + idx].take().unwrap();
+        self.values[start + idx..start + len].rotate_left(1);
+        let child = if self.nodes[node_id].is_leaf {
+            None
+        } else {
+            let start = node_id * self.order;
+            let child_id = self.children[start + child_idx];
+            self.children[start + child_idx..=start + len].rotate_left(1);
+            Some(child_id)
+        };
+        self.nodes[node_id].len -= 1;
+        (value, child)
+    }
+
+    /// Removes the value equals to the given one from the tree and returns it.
+    pub fn delete<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        let handle = self
+            .search_by(&|v: &T| self.cmp.compare(v.borrow(), value))
+            .ok()?;
+        Some(self.remove_handle(handle))
+    }
+
+    /// Removes the value at the position. A value in an internal node
+    /// is replaced by its predecessor, which is taken from a leaf.
+    fn remove_handle(&mut self, (node_id, idx): (usize, usize)) -> T {
+        let (mut value, leaf_id) = if self.nodes[node_id].is_leaf {
+            (self.take(node_id, idx, 0).0, node_id)
+        } else {
+            let leaf_id = self.most_right(self.child(node_id, idx));
+            let leaf_len = self.nodes[leaf_id].len;
+            (self.take(leaf_id, leaf_len - 1, 0).0, leaf_id)
+        };
+        if leaf_id != node_id {
+            let slot = self.slot((node_id, idx));
+            core::mem::swap(&mut value, self.values[slot].as_mut().unwrap());
+        }
+        self.len -= 1;
+        self.refill(leaf_id);
+        value
+    }
+
+    /// Rebalances the node which lost a value by rotating from a sibling,
+    /// or by merging with it, which goes on with the parent.
+    fn refill(&mut self, node_id: usize) {
+        let mut node_id = node_id;
+        loop {
+            let node = &self.nodes[node_id];
+            let parent_id = match node.parent {
+                Some(parent_id) => parent_id,
+                None => {
+                    // the emptied root gives way to its only child
+                    if node.len == 0 && !node.is_leaf {
+                        let child_id = self.child(node_id, 0);
+                        self.nodes[child_id].parent = None;
+                        self.root_id = child_id;
+                        self.free_node(node_id);
+                    }
+                    return;
+                }
+            };
+            if node.len >= self.min_values() {
+                return;
+            }
+
+            let child_idx = self.child_idx(parent_id, node_id);
+            if child_idx > 0 {
+                let left_id = self.child(parent_id, child_idx - 1);
+                if self.nodes[left_id].len > self.min_values() {
+                    self.rotate_right(parent_id, child_idx - 1);
+                    return;
+                }
+            }
+            if child_idx < self.nodes[parent_id].len {
+                let right_id = self.child(parent_id, child_idx + 1);
+                if self.nodes[right_id].len > self.min_values() {
+                    self.rotate_left(parent_id, child_idx);
+                    return;
+                }
+            }
+            self.merge(parent_id, child_idx.saturating_sub(1));
+            node_id = parent_id;
+        }
+    }
+
+    /// Moves the last value of the left child of the separator at `idx` up,
+    /// and the separator down to the front of the right child.
+    fn rotate_right(&mut self, parent_id: usize, idx: usize) {
+        let (left_id, right_id) = (self.child(parent_id, idx), self.child(parent_id, idx + 1));
+        let left_len = self.nodes[left_id].len;
+        let (value, child) = self.take(left_id, left_len - 1, left_len);
+        let slot = self.slot((parent_id, idx));
+        let separator = self.values[slot].replace(value).unwrap();
+        self.put(right_id, 0, separator, 0, child);
+    }
+
+    /// Moves the first value of the right child of the separator at `idx` up,
+    /// and the separator down to the end of the left child.
+    fn rotate_left(&mut self, parent_id: usize, idx: usize) {
+        let (left_id, right_id) = (self.child(parent_id, idx), self.child(parent_id, idx + 1));
+        let (value, child) = self.take(right_id, 0, 0);
+        let slot = self.slot((parent_id, idx));
+        let separator = self.values[slot].replace(value).unwrap();
+        let left_len = self.nodes[left_id].len;
+        self.put(left_id, left_len, separator, left_len + 1, child);
+    }
+
+    /// Merges the separator at `idx` and the right child of it into the left child.
+    fn merge(&mut self, parent_id: usize, idx: usize) {
+        let (left_id, right_id) = (self.child(parent_id, idx), self.child(parent_id, idx + 1));
+        let (separator, _) = self.take(parent_id, idx, idx + 1);
+
+        let left_len = self.nodes[left_id].len;
+        let right_len = self.nodes[right_id].len;
+        let (left_start, right_start) = (self.slot((left_id, 0)), self.slot((right_id, 0)));
+        self.values[left_start + left_len] = Some(separator);
+        for i in 0..right_len {
+            self.values[left_start + left_len + 1 + i] = self.values[right_start + i].take();
+        }
+        if !self.nodes[right_id].is_leaf {
+            for i in 0..=right_len {
+                let child_id = self.child(right_id, i);
+                self.children[left_id * self.order + left_len + 1 + i] = child_id;
+                self.nodes[child_id].parent = Some(left_id);
+            }
+        }
+        self.nodes[left_id].len = left_len + 1 + right_len;
+        self.nodes[right_id].len = 0;
+        self.free_node(right_id);
+    }
+
+    fn most_left(&self, node_id: usize) -> usize {
+        let mut cur = node_id;
+        while !self.nodes[cur].is_leaf {
+            cur = self.child(cur, 0);
+        }
+        cur
+    }
+
+    fn most_right(&self, node_id: usize) -> usize {
+        let mut cur = node_id;
+        while !self.nodes[cur].is_leaf {
+            cur = self.child(cur, self.nodes[cur].len);
+        }
+        cur
+    }
+
+    fn first_handle(&self) -> Option<(usize, usize)> {
+        if self.is_empty() {
+            return None;
+        }
+        Some((self.most_left(self.root_id), 0))
+    }
+
+    fn last_handle(&self) -> Option<(usize, usize)> {
+        if self.is_empty() {
+            return None;
+        }
+        let leaf_id = self.most_right(self.root_id);
+        Some((leaf_id, self.nodes[leaf_id].len - 1))
+    }
+
+    /// Finds the position of the value after the one at the given position.
+    fn next_handle(&self, (node_id, idx): (usize, usize)) -> Option<(usize, usize)> {
+        if !self.nodes[node_id].is_leaf {
+            return Some((self.most_left(self.child(node_id, idx + 1)), 0));
+        }
+        if idx + 1 < self.nodes[node_id].len {
+            return Some((node_id, idx + 1));
+        }
+        let mut cur = node_id;
+        while let Some(parent_id) = self.nodes[cur].parent {
+            let child_idx = self.child_idx(parent_id, cur);
+            if child_idx < self.nodes[parent_id].len {
+                return Some((parent_id, child_idx));
+            }
+            cur = parent_id;
+        }
+        None
+    }
+
+    /// Finds the position of the value before the one at the given position.
+    fn prev_handle(&self, (node_id, idx): (usize, usize)) -> Option<(usize, usize)> {
+        if !self.nodes[node_id].is_leaf {
+            let leaf_id = self.most_right(self.child(node_id, idx));
+            return Some((leaf_id, self.nodes[leaf_id].len - 1));
+        }
+        if idx > 0 {
+            return Some((node_id, idx - 1));
+        }
+        let mut cur = node_id;
+        while let Some(parent_id) = self.nodes[cur].parent {
+            let child_idx = self.child_idx(parent_id, cur);
+            if child_idx > 0 {
+                return Some((parent_id, child_idx - 1));
+            }
+            cur = parent_id;
+        }
+        None
+    }
+
+    /// Returns the smallest value in the tree.
+    pub fn first(&self) -> Option<&T> {
+        self.first_handle().map(|handle| self.value(handle))
+    }
+
+    /// Returns the largest value in the tree.
+    pub fn last(&self) -> Option<&T> {
+        self.last_handle().map(|handle| self.value(handle))
+    }
+
+    /// Gets an iterator that visits the values in the tree in ascending order.
+    pub fn iter(&self) -> Iter<'_, T, C> {
+        Iter {
+            tree: self,
+            front: self.first_handle(),
+            back: self.last_handle(),
+            remaining: self.len,
+        }
+    }
+}
+
+/// A lazy iterator over the values of a slab tree, in ascending order.
+///
+/// Created by [`Tree::iter`].
+#[derive(Debug)]
+pub struct Iter<'a, T, C = Natural> {
+    tree: &'a Tree<T, C>,
+    front: Option<(usize, usize)>,
+    back: Option<(usize, usize)>,
+    remaining: usize,
+}
+
+impl<'a, T, C> Clone for Iter<'a, T, C> {
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+impl<'a, T, C> Iterator for Iter<'a, T, C>
+where
+    C: Compare<T>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let handle = self.front?;
+        self.remaining -= 1;
+        self.front = self.tree.next_handle(handle);
+        Some(self.tree.value(handle))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T, C> DoubleEndedIterator for Iter<'a, T, C>
+where
+    C: Compare<T>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let handle = self.back?;
+        self.remaining -= 1;
+        self.back = self.tree.prev_handle(handle);
+        Some(self.tree.value(handle))
+    }
+}
+
+impl<'a, T, C> ExactSizeIterator for Iter<'a, T, C> where C: Compare<T> {}
+
+impl<'a, T, C> FusedIterator for Iter<'a, T, C> where C: Compare<T> {}
+
+impl<'a, T, C> IntoIterator for &'a Tree<T, C>
+where
+    C: Compare<T>,
+{
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, C>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks the node occupancy, the parent links, the order of the values
+    /// and that all leaves lie at the same depth.
+    fn check<T: Ord>(t: &Tree<T>) {
+        fn walk<T: Ord>(
+            t: &Tree<T>,
+            node_id: usize,
+            depth: usize,
+            leaf_depth: &mut Option<usize>,
+        ) -> usize {
+            let node = &t.nodes[node_id];
+            assert!(node.len <= t.capacity());
+            if node_id != t.root_id {
+                assert!(node.len >= t.min_values());
+            }
+            if node.is_leaf {
+                assert_eq!(*leaf_depth.get_or_insert(depth), depth);
+                return node.len;
+            }
+            let mut count = node.len;
+            for idx in 0..=node.len {
+                let child_id = t.child(node_id, idx);
+                assert_eq!(t.nodes[child_id].parent, Some(node_id));
+                count += walk(t, child_id, depth + 1, leaf_depth);
+            }
+            count
+        }
+        assert_eq!(t.nodes[t.root_id].parent, None);
+        assert_eq!(walk(t, t.root_id, 0, &mut None), t.len());
+        let values: Vec<_> = t.iter().collect();
+        assert!(values.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(values.len(), t.len());
+    }
+
+    #[test]
+    fn insert_get() {
+        for order in [3, 4, 5, 8, 33] {
+            let mut t = Tree::with_order(order);
+            for val in (0..1000).map(|v| v * 7919 % 1000) {
+                assert!(t.insert(val));
+            }
+            assert!(!t.insert(500));
+            check(&t);
+            assert_eq!(t.order(), order);
+            assert_eq!(t.len(), 1000);
+            assert_eq!(t.get(&777), Some(&777));
+            assert!(!t.contains(&1000));
+            assert_eq!(t.first(), Some(&0));
+            assert_eq!(t.last(), Some(&999));
+            assert!(t.iter().copied().eq(0..1000));
+            assert!(t.iter().rev().copied().eq((0..1000).rev()));
+        }
+    }
+
+    #[test]
+    fn delete() {
+        for order in [3, 4, 5, 8, 33] {
+            let mut t = Tree::with_order(order);
+            for val in 0..1000 {
+                t.insert(val);
+            }
+            for val in (0..1000).map(|v| v * 7919 % 1000).filter(|v| v % 3 != 0) {
+                assert_eq!(t.delete(&val), Some(val));
+                assert_eq!(t.delete(&val), None);
+            }
+            check(&t);
+            assert!(t.iter().copied().eq((0..1000).step_by(3)));
+
+            for val in (0..1000).step_by(3) {
+                assert_eq!(t.delete(&val), Some(val));
+            }
+            check(&t);
+            assert!(t.is_empty());
+            assert_eq!(t.first(), None);
+            assert_eq!(t.iter().next(), None);
+
+            // the freed nodes are reused
+            let nodes = t.nodes.len();
+            for val in 0..1000 {
+                t.insert(val);
+            }
+            check(&t);
+            assert_eq!(t.nodes.len(), nodes);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "order must be at least 3")]
+    fn with_order_too_small() {
+        Tree::<i32>::with_order(2);
+    }
+}
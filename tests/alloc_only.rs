@@ -0,0 +1,21 @@
+//! Exercises the tree through the public API only.
+//! Run with `cargo test --no-default-features` to check the build on `core` and `alloc`.
+
+use bt::arena::Tree;
+
+#[test]
+fn insert_get_delete() {
+    let mut t = Tree::<u32, 5>::default();
+    for val in (0..1000).map(|v| v * 7919 % 1000) {
+        assert!(t.insert(val));
+    }
+    assert_eq!(t.len(), 1000);
+    assert_eq!(t.get(&500), Some(&500));
+
+    for val in (0..1000).filter(|v| v % 2 == 0) {
+        assert_eq!(t.delete(&val), Some(val));
+    }
+    assert_eq!(t.get(&500), None);
+    assert!(t.iter().copied().eq((0..1000).filter(|v| v % 2 == 1)));
+    assert_eq!(t.validate(), Ok(()));
+}